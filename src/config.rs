@@ -0,0 +1,164 @@
+//! Configuration files that supply values missing from the command line.
+//!
+//! Files are in INI format. `key = value` sets the flag or option named
+//! `key`, `[name]` starts the section of subcommand `name` and
+//! `[name.sub]` the one of its own subcommand `sub`. Lines starting with
+//! `#` or `;` are comments, values may be enclosed in double quotes and
+//! a key given several times gives a multi-value option several values.
+
+use std::ffi::OsString;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use indexmap::IndexMap;
+
+#[derive(Debug, PartialEq)]
+pub struct Entry {
+    pub key: String,
+    pub value: String,
+    /// line of the file the entry is on, starting at 1
+    pub line: usize,
+}
+
+#[derive(Debug, Default, PartialEq)]
+pub struct Section {
+    /// line of the section header, 0 for the top-level section
+    pub line: usize,
+    pub entries: Vec<Entry>,
+}
+
+#[derive(Debug, Default)]
+pub struct Config {
+    pub path: PathBuf,
+    /// sections by name, the entries before any header are under `""`
+    pub sections: IndexMap<String, Section>,
+}
+
+impl Config {
+    pub fn load(path: &Path) -> Result<Config, String> {
+        let text = fs::read_to_string(path)
+            .map_err(|e| format!("cannot read config file {}: {}", path.display(), e))?;
+        let sections = parse(&text)
+            .map_err(|(line, message)| format!("{}:{}: {}", path.display(), line, message))?;
+        Ok(Config {
+            path: path.to_path_buf(),
+            sections,
+        })
+    }
+}
+
+/// Splits INI `text` into sections, an error comes with its line number.
+pub fn parse(text: &str) -> Result<IndexMap<String, Section>, (usize, String)> {
+    let mut sections = IndexMap::new();
+    sections.insert(String::new(), Section::default());
+    let mut current = String::new();
+
+    for (idx, line) in text.lines().enumerate() {
+        let number = idx + 1;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+            continue;
+        }
+        if let Some(header) = line.strip_prefix('[') {
+            let name = match header.strip_suffix(']') {
+                Some(name) => name.trim(),
+                None => return Err((number, "unterminated section header".to_string())),
+            };
+            if name.is_empty() {
+                return Err((number, "empty section name".to_string()));
+            }
+            current = name.to_string();
+            sections.entry(current.clone()).or_insert(Section {
+                line: number,
+                entries: vec![],
+            });
+            continue;
+        }
+        let (key, value) = match line.split_once('=') {
+            Some((key, value)) if !key.trim().is_empty() => (key.trim(), value.trim()),
+            _ => return Err((number, "expected key = value".to_string())),
+        };
+        let value = match value.strip_prefix('"').and_then(|v| v.strip_suffix('"')) {
+            Some(unquoted) => unquoted,
+            None => value,
+        };
+        sections[&current].entries.push(Entry {
+            key: key.to_string(),
+            value: value.to_string(),
+            line: number,
+        });
+    }
+    Ok(sections)
+}
+
+/// Looks for `<name>/config.ini` in the XDG config directories:
+/// `$XDG_CONFIG_HOME` (`~/.config` by default), then each of
+/// `$XDG_CONFIG_DIRS` (`/etc/xdg` by default).
+pub fn find(name: &str, env_var: impl Fn(&str) -> Option<OsString>) -> Option<PathBuf> {
+    let set = |var: &str| env_var(var).filter(|value| !value.is_empty());
+    let mut dirs = vec![];
+    match set("XDG_CONFIG_HOME") {
+        Some(dir) => dirs.push(PathBuf::from(dir)),
+        None => {
+            if let Some(home) = set("HOME") {
+                dirs.push(Path::new(&home).join(".config"));
+            }
+        }
+    }
+    let system = set("XDG_CONFIG_DIRS").unwrap_or_else(|| OsString::from("/etc/xdg"));
+    dirs.extend(std::env::split_paths(&system));
+    dirs.into_iter()
+        .map(|dir| dir.join(name).join("config.ini"))
+        .find(|path| path.is_file())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse, Entry, Section};
+
+    fn entry(key: &str, value: &str, line: usize) -> Entry {
+        Entry {
+            key: key.to_string(),
+            value: value.to_string(),
+            line,
+        }
+    }
+
+    #[test]
+    fn test_parse() {
+        let sections = parse(
+            "# top level\ncolor = red\n\n[remote.add]\n; comment\nname=\"a = b\"\nname = c\n[ remote.add ]\nx =\n",
+        )
+        .unwrap();
+        assert_eq!(
+            sections[""],
+            Section {
+                line: 0,
+                entries: vec![entry("color", "red", 2)]
+            }
+        );
+        assert_eq!(
+            sections["remote.add"],
+            Section {
+                line: 4,
+                entries: vec![
+                    entry("name", "a = b", 6),
+                    entry("name", "c", 7),
+                    entry("x", "", 9)
+                ]
+            }
+        );
+        assert_eq!(sections.len(), 2);
+
+        assert_eq!(
+            parse("a = 1\n[sub\n"),
+            Err((2, "unterminated section header".to_string()))
+        );
+        assert_eq!(parse("[]"), Err((1, "empty section name".to_string())));
+        assert_eq!(
+            parse("a = 1\nb\n"),
+            Err((2, "expected key = value".to_string()))
+        );
+        assert_eq!(parse(" = 1"), Err((1, "expected key = value".to_string())));
+    }
+}
@@ -7,36 +7,201 @@
 //     display ungrouped options before grouped ones
 //     display nice error messages
 //     validation
-//     support argument allowed values, default values
-//     handle positional separator
 
 use std::convert::From;
+use std::ffi::{OsStr, OsString};
+use std::fs::File;
+use std::io::{self, Read, Write};
+use std::path::Path;
+#[cfg(feature = "config")]
+use std::path::PathBuf;
 
 #[macro_use]
 extern crate derivative;
 use indexmap::{map::Entry, IndexMap, IndexSet};
 
-mod parser;
+#[cfg(feature = "config")]
+pub mod config;
+pub mod parser;
+pub mod response_file;
+mod shell_words;
+pub use parser::Syntax;
 
 #[macro_use]
 extern crate derive_builder;
 
 type ValueValidator = fn(&str) -> Result<(), String>;
 type MultiValueValidator = fn(&Vec<String>) -> Result<(), String>;
-type SubCommandValidator = fn(&SubCommandDef) -> Result<(), String>;
+type SubCommandValidator = fn(&App) -> Result<(), String>;
 type AppValidator = fn(&App) -> Result<(), String>;
 
+/// Value that stands for stdin or stdout when the option or argument allows it.
+const STDIO: &str = "-";
+/// Name and long name of the flag every app starts with.
+const VERBOSE: &str = "verbose";
+/// Short name of the flag every app starts with.
+const VERBOSE_SHORT: char = 'V';
+
+fn dash_values(allow_hyphen_values: bool, allow_negative_numbers: bool) -> parser::DashValues {
+    if allow_hyphen_values {
+        parser::DashValues::All
+    } else if allow_negative_numbers {
+        parser::DashValues::NegativeNumbers
+    } else {
+        parser::DashValues::None
+    }
+}
+
+/// Validators work on text, values that are not valid UTF-8 are passed
+/// to them with invalid sequences replaced.
+fn lossy_values(values: &[OsString]) -> Vec<String> {
+    values
+        .iter()
+        .map(|value| value.to_string_lossy().into_owned())
+        .collect()
+}
+
+/// Picks the default of a value def: the first of `defaults_if` whose
+/// flag, option or argument was given, otherwise `default`. Only whether
+/// `other` was given counts, not the value it was given.
+fn pick_default<'d, T>(
+    app: &App,
+    defaults_if: &'d [(String, T)],
+    default: Option<&'d T>,
+) -> Option<&'d T> {
+    defaults_if
+        .iter()
+        .find(|(other, _)| app.is_present(other))
+        .map(|(_, value)| value)
+        .or(default)
+}
+
+fn env_help(env: &str) -> String {
+    format!(" [env: {}]", env)
+}
+
+/// Reads a boolean from an environment variable: `1`, `true`, `yes`
+/// and `on` are true, `0`, `false`, `no`, `off` and nothing are false,
+/// in any case.
+pub fn parse_bool(value: &OsStr) -> Option<bool> {
+    match value.to_str()?.to_lowercase().as_str() {
+        "1" | "true" | "yes" | "on" => Some(true),
+        "" | "0" | "false" | "no" | "off" => Some(false),
+        _ => None,
+    }
+}
+
+fn default_help(values: &[String]) -> String {
+    format!(" [default: {}]", values.join(", "))
+}
+
+/// A value an option or argument accepts, see their `possible_values` setter.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PossibleValue {
+    pub name: String,
+    pub help: Option<String>,
+}
+
+impl PossibleValue {
+    pub fn new(name: String, help: Option<String>) -> PossibleValue {
+        PossibleValue { name, help }
+    }
+}
+
+/// How a flag, option or argument relates to others at its level.
+#[derive(Builder, Clone, Debug, Default)]
+pub struct Relations {
+    #[builder(default = "vec![]")]
+    conflicts_with: Vec<String>,
+    #[builder(default = "vec![]")]
+    requires: Vec<String>,
+    #[builder(default = "vec![]")]
+    required_unless_present: Vec<String>,
+    /// `(other, value)` pairs, see `required_if_eq`
+    #[builder(default = "vec![]")]
+    required_if: Vec<(String, String)>,
+    /// whichever of the two is given last wins
+    #[builder(default = "vec![]")]
+    overrides_with: Vec<String>,
+}
+
+impl Relations {
+    /// Names of the flags, options and arguments referred to.
+    fn others(&self) -> impl Iterator<Item = &str> {
+        self.conflicts_with
+            .iter()
+            .chain(&self.requires)
+            .chain(&self.required_unless_present)
+            .chain(self.required_if.iter().map(|(other, _)| other))
+            .chain(&self.overrides_with)
+            .map(String::as_str)
+    }
+
+    fn help(&self) -> String {
+        let mut s = String::new();
+        let mut note = |title: &str, names: Vec<String>| {
+            if !names.is_empty() {
+                s.push_str(&format!(" [{}: {}]", title, names.join(", ")));
+            }
+        };
+        note("conflicts with", self.conflicts_with.to_vec());
+        note("requires", self.requires.to_vec());
+        note("required unless", self.required_unless_present.to_vec());
+        note(
+            "required if",
+            self.required_if
+                .iter()
+                .map(|(other, value)| format!("{}={}", other, value))
+                .collect(),
+        );
+        note("overrides", self.overrides_with.to_vec());
+        s
+    }
+}
+
+impl RelationsBuilder {
+    pub fn new() -> RelationsBuilder {
+        RelationsBuilder::default()
+    }
+
+    /// Makes the flag, option or argument required when option or
+    /// argument `other` was given `value`.
+    pub fn required_if_eq(&mut self, other: String, value: String) -> &mut Self {
+        self.required_if
+            .get_or_insert_with(Vec::new)
+            .push((other, value));
+        self
+    }
+}
+
+/// Placeholder for a value that must be one of `possible_values`: `{fast|slow}`.
+fn choices_part(possible_values: &[PossibleValue]) -> String {
+    let names: Vec<&str> = possible_values.iter().map(|pv| pv.name.as_str()).collect();
+    format!("{{{}}}", names.join("|"))
+}
+
+/// A line for each of `possible_values` that has help.
+fn choices_help(possible_values: &[PossibleValue]) -> String {
+    let mut s = String::new();
+    for possible_value in possible_values {
+        if let Some(help) = &possible_value.help {
+            s.push_str(&format!("        {}: {}\n", possible_value.name, help));
+        }
+    }
+    s
+}
+
 #[derive(Clone, Default)]
 pub struct ArgumentDefs(pub Vec<ArgumentDef>);
 
 impl ArgumentDefs {
     pub fn validate(
         &self,
-        single_value_arguments: &IndexMap<String, String>,
-        multi_value_arguments: &IndexMap<String, Vec<String>>,
+        single_value_arguments: &IndexMap<String, OsString>,
+        multi_value_arguments: &IndexMap<String, Vec<OsString>>,
     ) -> Result<(), String> {
         for argument_def in &self.0 {
-            argument_def.validate(&single_value_arguments, &multi_value_arguments)?
+            argument_def.validate(single_value_arguments, multi_value_arguments)?
         }
         Ok(())
     }
@@ -57,14 +222,14 @@ impl FlagDefs {
                 FlagDef::BooleanFlagDef(ref bfd) => {
                     if let Some(v) = bfd.short {
                         if v == *ch {
-                            return Some(&fd);
+                            return Some(fd);
                         }
                     }
                 }
                 FlagDef::CountedFlagDef(ref cfd) => {
                     if let Some(v) = cfd.short {
                         if v == *ch {
-                            return Some(&fd);
+                            return Some(fd);
                         }
                     }
                 }
@@ -79,14 +244,14 @@ impl FlagDefs {
                 FlagDef::BooleanFlagDef(ref bfd) => {
                     if let Some(v) = &bfd.long {
                         if v == param {
-                            return Some(&fd);
+                            return Some(fd);
                         }
                     }
                 }
                 FlagDef::CountedFlagDef(ref cfd) => {
                     if let Some(v) = &cfd.long {
                         if v == param {
-                            return Some(&fd);
+                            return Some(fd);
                         }
                     }
                 }
@@ -95,13 +260,27 @@ impl FlagDefs {
         None
     }
 
+    pub fn longs(&self) -> Vec<&str> {
+        self.0
+            .iter()
+            .filter_map(|fd| match fd {
+                FlagDef::BooleanFlagDef(bfd) => bfd.long.as_deref(),
+                FlagDef::CountedFlagDef(cfd) => cfd.long.as_deref(),
+            })
+            .collect()
+    }
+
+    pub fn shorts(&self) -> Vec<char> {
+        self.0.iter().filter_map(FlagDef::short).collect()
+    }
+
     pub fn validate(
         &self,
         boolean_flags: &IndexMap<String, bool>,
         counted_flags: &IndexMap<String, u64>,
     ) -> Result<(), String> {
         for flag_def in &self.0 {
-            flag_def.validate(&boolean_flags, &counted_flags)?
+            flag_def.validate(boolean_flags, counted_flags)?
         }
         Ok(())
     }
@@ -116,12 +295,12 @@ impl OptionDefs {
             match item {
                 OptionDef::SingleValue(svod) => {
                     if svod.name == name {
-                        return Some(&item);
+                        return Some(item);
                     }
                 }
                 OptionDef::MultiValue(mvod) => {
                     if mvod.name == name {
-                        return Some(&item);
+                        return Some(item);
                     }
                 }
             }
@@ -135,14 +314,14 @@ impl OptionDefs {
                 OptionDef::SingleValue(ref svod) => {
                     if let Some(v) = svod.short {
                         if v == *ch {
-                            return Some(&od);
+                            return Some(od);
                         }
                     }
                 }
                 OptionDef::MultiValue(ref mvod) => {
                     if let Some(v) = mvod.short {
                         if v == *ch {
-                            return Some(&od);
+                            return Some(od);
                         }
                     }
                 }
@@ -157,14 +336,14 @@ impl OptionDefs {
                 OptionDef::SingleValue(ref svod) => {
                     if let Some(v) = &svod.long {
                         if v == param {
-                            return Some(&od);
+                            return Some(od);
                         }
                     }
                 }
                 OptionDef::MultiValue(ref mvod) => {
                     if let Some(v) = &mvod.long {
                         if v == param {
-                            return Some(&od);
+                            return Some(od);
                         }
                     }
                 }
@@ -173,13 +352,27 @@ impl OptionDefs {
         None
     }
 
+    pub fn longs(&self) -> Vec<&str> {
+        self.0
+            .iter()
+            .filter_map(|od| match od {
+                OptionDef::SingleValue(svod) => svod.long.as_deref(),
+                OptionDef::MultiValue(mvod) => mvod.long.as_deref(),
+            })
+            .collect()
+    }
+
+    pub fn shorts(&self) -> Vec<char> {
+        self.0.iter().filter_map(OptionDef::short).collect()
+    }
+
     pub fn validate(
         &self,
-        single_value_options: &IndexMap<String, String>,
-        multi_value_options: &IndexMap<String, Vec<String>>,
+        single_value_options: &IndexMap<String, OsString>,
+        multi_value_options: &IndexMap<String, Vec<OsString>>,
     ) -> Result<(), String> {
         for option_def in &self.0 {
-            option_def.validate(&single_value_options, &multi_value_options)?
+            option_def.validate(single_value_options, multi_value_options)?
         }
         Ok(())
     }
@@ -228,6 +421,15 @@ pub struct BooleanFlagDef {
     help: Option<String>,
     #[builder(default = "false")]
     required: bool,
+    /// also accepted in subcommands, its value goes to the top-level app
+    #[builder(default = "false")]
+    global: bool,
+    /// environment variable read when the flag is not given,
+    /// see `parse_bool`
+    #[builder(default = "None")]
+    env: Option<String>,
+    #[builder(default = "Relations::default()")]
+    relations: Relations,
 }
 
 impl BooleanFlagDef {
@@ -235,6 +437,33 @@ impl BooleanFlagDef {
         FlagDef::BooleanFlagDef(self)
     }
 
+    pub fn get_help(&self) -> String {
+        let mut s: String = String::new();
+        s.push_str("    ");
+        match (&self.short, &self.long) {
+            (Some(short), None) => s.push_str(&format!("-{}", short)),
+            (None, Some(long)) => s.push_str(&format!("--{}", long)),
+            (Some(short), Some(long)) => s.push_str(&format!("-{}, --{}", short, long)),
+            // only set through its environment variable
+            (None, None) => {
+                if let Some(env) = &self.env {
+                    s.push_str(env_help(env).trim_start());
+                }
+            }
+        }
+        if let Some(help) = &self.help {
+            s.push_str(&format!(" {}", &help));
+        }
+        // already shown in place of the names if there are none
+        let named = self.short.is_some() || self.long.is_some();
+        if let Some(env) = self.env.as_ref().filter(|_| named) {
+            s.push_str(&env_help(env));
+        }
+        s.push_str(&self.relations.help());
+        s.push('\n');
+        s
+    }
+
     pub fn validate(&self, boolean_flags: &IndexMap<String, bool>) -> Result<(), String> {
         if self.required && !boolean_flags.contains_key(&self.name) {
             return Err(format!("flag {} is required", self.name));
@@ -265,6 +494,11 @@ pub struct CountedFlagDef {
     min_occurences: u64,
     #[builder(default = "None")]
     max_occurences: Option<u64>,
+    /// also accepted in subcommands, its value goes to the top-level app
+    #[builder(default = "false")]
+    global: bool,
+    #[builder(default = "Relations::default()")]
+    relations: Relations,
 }
 
 impl CountedFlagDef {
@@ -272,6 +506,24 @@ impl CountedFlagDef {
         FlagDef::CountedFlagDef(self)
     }
 
+    pub fn get_help(&self) -> String {
+        let mut s: String = String::new();
+        s.push_str("    ");
+        match (&self.short, &self.long) {
+            (Some(short), None) => s.push_str(&format!("-{}", short)),
+            (None, Some(long)) => s.push_str(&format!("--{}", long)),
+            (Some(short), Some(long)) => s.push_str(&format!("-{}, --{}", short, long)),
+            (None, None) => unreachable!("checked when the flag is built"),
+        }
+        s.push_str(" [...]");
+        if let Some(help) = &self.help {
+            s.push_str(&format!(" {}", &help));
+        }
+        s.push_str(&self.relations.help());
+        s.push('\n');
+        s
+    }
+
     pub fn validate(&self, counted_flags: &IndexMap<String, u64>) -> Result<(), String> {
         let cnt = match counted_flags.get(&self.name) {
             Some(v) => *v,
@@ -337,14 +589,56 @@ pub enum FlagDef {
 }
 
 impl FlagDef {
+    pub fn name(&self) -> String {
+        match self {
+            FlagDef::BooleanFlagDef(f) => f.name.clone(),
+            FlagDef::CountedFlagDef(f) => f.name.clone(),
+        }
+    }
+
+    fn is_global(&self) -> bool {
+        match self {
+            FlagDef::BooleanFlagDef(f) => f.global,
+            FlagDef::CountedFlagDef(f) => f.global,
+        }
+    }
+
+    fn short(&self) -> Option<char> {
+        match self {
+            FlagDef::BooleanFlagDef(f) => f.short,
+            FlagDef::CountedFlagDef(f) => f.short,
+        }
+    }
+
+    fn long(&self) -> Option<&str> {
+        match self {
+            FlagDef::BooleanFlagDef(f) => f.long.as_deref(),
+            FlagDef::CountedFlagDef(f) => f.long.as_deref(),
+        }
+    }
+
+    pub fn get_help(&self) -> String {
+        match self {
+            FlagDef::BooleanFlagDef(f) => f.get_help(),
+            FlagDef::CountedFlagDef(f) => f.get_help(),
+        }
+    }
+
+    fn relations(&self) -> (&str, &Relations) {
+        match self {
+            FlagDef::BooleanFlagDef(f) => (&f.name, &f.relations),
+            FlagDef::CountedFlagDef(f) => (&f.name, &f.relations),
+        }
+    }
+
     pub fn validate(
         &self,
         boolean_flags: &IndexMap<String, bool>,
         counted_flags: &IndexMap<String, u64>,
     ) -> Result<(), String> {
         match self {
-            FlagDef::BooleanFlagDef(bfd) => bfd.validate(&boolean_flags),
-            FlagDef::CountedFlagDef(cfd) => cfd.validate(&counted_flags),
+            FlagDef::BooleanFlagDef(bfd) => bfd.validate(boolean_flags),
+            FlagDef::CountedFlagDef(cfd) => cfd.validate(counted_flags),
         }
     }
 }
@@ -373,9 +667,37 @@ pub struct SingleValueOptionDef {
     help: Option<String>,
     #[builder(default = "false")]
     required: bool,
+    /// `-` stands for stdin or stdout rather than a file name
+    #[builder(default = "false")]
+    stdio: bool,
+    #[builder(default = "false")]
+    allow_hyphen_values: bool,
+    #[builder(default = "false")]
+    allow_negative_numbers: bool,
     #[builder(default = "vec![]")]
     #[derivative(Debug = "ignore")]
     validators: Vec<ValueValidator>,
+    /// also accepted in subcommands, its value goes to the top-level app
+    #[builder(default = "false")]
+    global: bool,
+    /// environment variable read when the option is not given
+    #[builder(default = "None")]
+    env: Option<String>,
+    /// value used when the option is not given
+    #[builder(default = "None")]
+    default_value: Option<String>,
+    /// `(other, value)` pairs, `value` is the default when flag, option
+    /// or argument `other` was given, see `default_if`
+    #[builder(default = "vec![]")]
+    defaults_if: Vec<(String, String)>,
+    /// the only values accepted
+    #[builder(default = "vec![]")]
+    possible_values: Vec<PossibleValue>,
+    /// possible values match regardless of case
+    #[builder(default = "false")]
+    ignore_case: bool,
+    #[builder(default = "Relations::default()")]
+    relations: Relations,
 }
 
 impl SingleValueOptionDef {
@@ -386,24 +708,42 @@ impl SingleValueOptionDef {
             (Some(short), None) => s.push_str(&format!("-{}", short)),
             (None, Some(long)) => s.push_str(&format!("--{}", long)),
             (Some(short), Some(long)) => s.push_str(&format!("-{}, --{}", short, long)),
-            (None, None) => panic!(
-                "option {} must have either short or long value provided",
-                self.name
-            ),
+            // only set through its environment variable
+            (None, None) => {
+                if let Some(env) = &self.env {
+                    s.push_str(env_help(env).trim_start());
+                }
+            }
+        }
+        if !self.possible_values.is_empty() {
+            s.push_str(&format!(" {}", choices_part(&self.possible_values)));
         }
         if let Some(help) = &self.help {
             s.push_str(&format!(" {}", &help));
         }
-        s.push_str("\n");
+        // already shown in place of the names if there are none
+        let named = self.short.is_some() || self.long.is_some();
+        if let Some(env) = self.env.as_ref().filter(|_| named) {
+            s.push_str(&env_help(env));
+        }
+        if let Some(value) = &self.default_value {
+            s.push_str(&default_help(std::slice::from_ref(value)));
+        }
+        s.push_str(&self.relations.help());
+        s.push('\n');
+        s.push_str(&choices_help(&self.possible_values));
         s
     }
 
-    pub fn validate(&self, single_value_options: &IndexMap<String, String>) -> Result<(), String> {
+    pub fn validate(
+        &self,
+        single_value_options: &IndexMap<String, OsString>,
+    ) -> Result<(), String> {
         match &single_value_options.get(&self.name) {
             None => (),
-            Some(ref value) => {
+            Some(value) => {
                 for validator in &self.validators {
-                    validator(&value)?;
+                    validator(&value.to_string_lossy())?;
                 }
             }
         }
@@ -421,6 +761,15 @@ impl SingleValueOptionDefBuilder {
         svodb.name(name).short(short).long(long);
         svodb
     }
+
+    /// Makes `value` the default when `other` was given, whatever its
+    /// value. The first such default that applies wins over `default_value`.
+    pub fn default_if(&mut self, other: String, value: String) -> &mut Self {
+        self.defaults_if
+            .get_or_insert_with(Vec::new)
+            .push((other, value));
+        self
+    }
 }
 
 #[derive(Builder, Clone, Derivative)]
@@ -437,9 +786,37 @@ pub struct MultiValueOptionDef {
     min_occurences: u64,
     #[builder(default = "None")]
     max_occurences: Option<u64>,
+    #[builder(default = "false")]
+    allow_hyphen_values: bool,
+    #[builder(default = "false")]
+    allow_negative_numbers: bool,
     #[builder(default = "vec![]")]
     #[derivative(Debug = "ignore")]
     validators: Vec<MultiValueValidator>,
+    /// also accepted in subcommands, its value goes to the top-level app
+    #[builder(default = "false")]
+    global: bool,
+    /// environment variable read when the option is not given
+    #[builder(default = "None")]
+    env: Option<String>,
+    /// separates the values in the environment variable
+    #[builder(default = "Some(',')")]
+    env_delimiter: Option<char>,
+    /// values used when the option is not given
+    #[builder(default = "vec![]")]
+    default_values: Vec<String>,
+    /// `(other, values)` pairs, `values` are the default when flag, option
+    /// or argument `other` was given, see `default_if`
+    #[builder(default = "vec![]")]
+    defaults_if: Vec<(String, Vec<String>)>,
+    /// the only values accepted
+    #[builder(default = "vec![]")]
+    possible_values: Vec<PossibleValue>,
+    /// possible values match regardless of case
+    #[builder(default = "false")]
+    ignore_case: bool,
+    #[builder(default = "Relations::default()")]
+    relations: Relations,
 }
 
 impl MultiValueOptionDef {
@@ -450,29 +827,36 @@ impl MultiValueOptionDef {
             (Some(short), None) => s.push_str(&format!("-{}", short)),
             (None, Some(long)) => s.push_str(&format!("--{}", long)),
             (Some(short), Some(long)) => s.push_str(&format!("-{}, --{}", short, long)),
-            (None, None) => panic!(
-                "option {} must have either short or long value provided",
-                self.name
-            ),
+            // only set through its environment variable
+            (None, None) => {
+                if let Some(env) = &self.env {
+                    s.push_str(env_help(env).trim_start());
+                }
+            }
         }
+        let placeholder = if self.possible_values.is_empty() {
+            self.name.to_uppercase()
+        } else {
+            choices_part(&self.possible_values)
+        };
         match &(self.min_occurences, self.max_occurences) {
             (v1, None) => match v1 {
-                0 => s.push_str(&format!(" [{}] [...]", self.name.to_uppercase())),
+                0 => s.push_str(&format!(" [{}] [...]", placeholder)),
                 _ => {
                     for _ in 0..*v1 {
-                        s.push_str(&format!(" {}", self.name.to_uppercase()))
+                        s.push_str(&format!(" {}", placeholder))
                     }
                     s.push_str(" [...]");
                 }
             },
             (v1, Some(v2)) => {
                 for _ in 0..*v1 {
-                    s.push_str(&format!(" {}", self.name.to_uppercase()))
+                    s.push_str(&format!(" {}", placeholder))
                 }
 
                 if v1 < v2 {
                     for _ in *v1..*v2 {
-                        s.push_str(&format!(" [{}]", self.name.to_uppercase()))
+                        s.push_str(&format!(" [{}]", placeholder))
                     }
                 }
             }
@@ -480,17 +864,28 @@ impl MultiValueOptionDef {
         if let Some(help) = &self.help {
             s.push_str(&format!(" {}", &help));
         }
-        s.push_str("\n");
+        // already shown in place of the names if there are none
+        let named = self.short.is_some() || self.long.is_some();
+        if let Some(env) = self.env.as_ref().filter(|_| named) {
+            s.push_str(&env_help(env));
+        }
+        if !self.default_values.is_empty() {
+            s.push_str(&default_help(&self.default_values));
+        }
+        s.push_str(&self.relations.help());
+        s.push('\n');
+        s.push_str(&choices_help(&self.possible_values));
         s
     }
 
     pub fn validate(
         &self,
-        multi_value_options: &IndexMap<String, Vec<String>>,
+        multi_value_options: &IndexMap<String, Vec<OsString>>,
     ) -> Result<(), String> {
         match &multi_value_options.get(&self.name) {
             None => (),
-            Some(ref values) => {
+            Some(values) => {
+                let values = lossy_values(values);
                 for validator in &self.validators {
                     validator(&values)?;
                 }
@@ -510,6 +905,15 @@ impl MultiValueOptionDefBuilder {
         mvodb.name(name).short(short).long(long);
         mvodb
     }
+
+    /// Makes `values` the default when `other` was given, whatever its
+    /// value. The first such default that applies wins over `default_values`.
+    pub fn default_if(&mut self, other: String, values: Vec<String>) -> &mut Self {
+        self.defaults_if
+            .get_or_insert_with(Vec::new)
+            .push((other, values));
+        self
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -519,6 +923,17 @@ pub enum OptionDef {
 }
 
 impl OptionDef {
+    fn dash_values(&self) -> parser::DashValues {
+        match self {
+            OptionDef::SingleValue(o) => {
+                dash_values(o.allow_hyphen_values, o.allow_negative_numbers)
+            }
+            OptionDef::MultiValue(o) => {
+                dash_values(o.allow_hyphen_values, o.allow_negative_numbers)
+            }
+        }
+    }
+
     pub fn name(&self) -> String {
         match self {
             OptionDef::SingleValue(o) => o.name.clone(),
@@ -526,6 +941,27 @@ impl OptionDef {
         }
     }
 
+    fn is_global(&self) -> bool {
+        match self {
+            OptionDef::SingleValue(o) => o.global,
+            OptionDef::MultiValue(o) => o.global,
+        }
+    }
+
+    fn short(&self) -> Option<char> {
+        match self {
+            OptionDef::SingleValue(o) => o.short,
+            OptionDef::MultiValue(o) => o.short,
+        }
+    }
+
+    fn long(&self) -> Option<&str> {
+        match self {
+            OptionDef::SingleValue(o) => o.long.as_deref(),
+            OptionDef::MultiValue(o) => o.long.as_deref(),
+        }
+    }
+
     pub fn get_help(&self) -> String {
         match self {
             OptionDef::SingleValue(o) => o.get_help(),
@@ -533,14 +969,58 @@ impl OptionDef {
         }
     }
 
+    fn relations(&self) -> (&str, &Relations) {
+        match self {
+            OptionDef::SingleValue(o) => (&o.name, &o.relations),
+            OptionDef::MultiValue(o) => (&o.name, &o.relations),
+        }
+    }
+
+    /// Values the option accepts, empty if it accepts any.
+    pub fn possible_values(&self) -> &[PossibleValue] {
+        match self {
+            OptionDef::SingleValue(o) => &o.possible_values,
+            OptionDef::MultiValue(o) => &o.possible_values,
+        }
+    }
+
+    /// How values are compared with the possible values, `matching`
+    /// of the app unless the option ignores case itself.
+    fn value_matching(&self, matching: Matching) -> Matching {
+        let ignore_case = match self {
+            OptionDef::SingleValue(o) => o.ignore_case,
+            OptionDef::MultiValue(o) => o.ignore_case,
+        };
+        Matching {
+            ignore_case: matching.ignore_case || ignore_case,
+            ..matching
+        }
+    }
+
+    /// Values the option gets when it was not given, if any.
+    fn default_values(&self, app: &App) -> Option<Vec<OsString>> {
+        let values = match self {
+            OptionDef::SingleValue(o) if !app.single_value_options.contains_key(&o.name) => {
+                pick_default(app, &o.defaults_if, o.default_value.as_ref())
+                    .map(std::slice::from_ref)
+            }
+            OptionDef::MultiValue(o) if !app.multi_value_options.contains_key(&o.name) => {
+                let default = Some(&o.default_values).filter(|values| !values.is_empty());
+                pick_default(app, &o.defaults_if, default).map(Vec::as_slice)
+            }
+            _ => None,
+        };
+        values.map(|values| values.iter().map(OsString::from).collect())
+    }
+
     pub fn validate(
         &self,
-        single_value_options: &IndexMap<String, String>,
-        multi_value_options: &IndexMap<String, Vec<String>>,
+        single_value_options: &IndexMap<String, OsString>,
+        multi_value_options: &IndexMap<String, Vec<OsString>>,
     ) -> Result<(), String> {
         match self {
-            OptionDef::SingleValue(o) => o.validate(&single_value_options),
-            OptionDef::MultiValue(o) => o.validate(&multi_value_options),
+            OptionDef::SingleValue(o) => o.validate(single_value_options),
+            OptionDef::MultiValue(o) => o.validate(multi_value_options),
         }
     }
 
@@ -581,9 +1061,31 @@ pub struct SingleValueArgumentDef {
     help: Option<String>,
     #[builder(default = "false")]
     required: bool,
+    /// `-` stands for stdin or stdout rather than a file name
+    #[builder(default = "false")]
+    stdio: bool,
+    #[builder(default = "false")]
+    allow_hyphen_values: bool,
+    #[builder(default = "false")]
+    allow_negative_numbers: bool,
     #[builder(default = "vec![]")]
     #[derivative(Debug = "ignore")]
     validators: Vec<ValueValidator>,
+    /// value used when the argument is not given
+    #[builder(default = "None")]
+    default_value: Option<String>,
+    /// `(other, value)` pairs, `value` is the default when flag, option
+    /// or argument `other` was given, see `default_if`
+    #[builder(default = "vec![]")]
+    defaults_if: Vec<(String, String)>,
+    /// the only values accepted
+    #[builder(default = "vec![]")]
+    possible_values: Vec<PossibleValue>,
+    /// possible values match regardless of case
+    #[builder(default = "false")]
+    ignore_case: bool,
+    #[builder(default = "Relations::default()")]
+    relations: Relations,
 }
 
 impl SingleValueArgumentDef {
@@ -593,7 +1095,7 @@ impl SingleValueArgumentDef {
 
     pub fn validate(
         &self,
-        single_value_arguments: &IndexMap<String, String>,
+        single_value_arguments: &IndexMap<String, OsString>,
     ) -> Result<(), String> {
         match &single_value_arguments.get(&self.name) {
             None => {
@@ -606,9 +1108,9 @@ impl SingleValueArgumentDef {
                     Ok(())
                 }
             }
-            Some(ref value) => {
+            Some(value) => {
                 for validator in &self.validators {
-                    validator(&value)?;
+                    validator(&value.to_string_lossy())?;
                 }
                 Ok(())
             }
@@ -622,6 +1124,15 @@ impl SingleValueArgumentDefBuilder {
         svadb.name(name);
         svadb
     }
+
+    /// Makes `value` the default when `other` was given, whatever its
+    /// value. The first such default that applies wins over `default_value`.
+    pub fn default_if(&mut self, other: String, value: String) -> &mut Self {
+        self.defaults_if
+            .get_or_insert_with(Vec::new)
+            .push((other, value));
+        self
+    }
 }
 
 #[derive(Builder, Clone, Derivative)]
@@ -637,6 +1148,28 @@ pub struct MultiValueArgumentDef {
     min_occurences: u64,
     #[builder(default = "None")]
     max_occurences: Option<u64>,
+    #[builder(default = "false")]
+    allow_hyphen_values: bool,
+    #[builder(default = "false")]
+    allow_negative_numbers: bool,
+    /// hand the values out as a `ValueStream` from `AppDef::parse_args_iter`
+    #[builder(default = "false")]
+    stream: bool,
+    /// values used when the argument is not given
+    #[builder(default = "vec![]")]
+    default_values: Vec<String>,
+    /// `(other, values)` pairs, `values` are the default when flag, option
+    /// or argument `other` was given, see `default_if`
+    #[builder(default = "vec![]")]
+    defaults_if: Vec<(String, Vec<String>)>,
+    /// the only values accepted
+    #[builder(default = "vec![]")]
+    possible_values: Vec<PossibleValue>,
+    /// possible values match regardless of case
+    #[builder(default = "false")]
+    ignore_case: bool,
+    #[builder(default = "Relations::default()")]
+    relations: Relations,
 }
 
 impl MultiValueArgumentDef {
@@ -646,11 +1179,19 @@ impl MultiValueArgumentDef {
 
     pub fn validate(
         &self,
-        multi_value_arguments: &IndexMap<String, Vec<String>>,
+        multi_value_arguments: &IndexMap<String, Vec<OsString>>,
     ) -> Result<(), String> {
+        let cnt = multi_value_arguments.get(&self.name).map_or(0, Vec::len) as u64;
+        if cnt < self.min_occurences {
+            return Err(format!(
+                "positional argument {} requires at least {} value(s), {} given",
+                self.name, self.min_occurences, cnt
+            ));
+        }
         match &multi_value_arguments.get(&self.name) {
             None => (),
-            Some(ref values) => {
+            Some(values) => {
+                let values = lossy_values(values);
                 for validator in &self.validators {
                     validator(&values)?;
                 }
@@ -666,6 +1207,15 @@ impl MultiValueArgumentDefBuilder {
         mvadb.name(name);
         mvadb
     }
+
+    /// Makes `values` the default when `other` was given, whatever its
+    /// value. The first such default that applies wins over `default_values`.
+    pub fn default_if(&mut self, other: String, values: Vec<String>) -> &mut Self {
+        self.defaults_if
+            .get_or_insert_with(Vec::new)
+            .push((other, values));
+        self
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -675,40 +1225,149 @@ pub enum ArgumentDef {
 }
 
 impl ArgumentDef {
-    pub fn new_single_value(name: String) -> SingleValueArgumentDefBuilder {
-        SingleValueArgumentDefBuilder::new(name)
+    pub fn name(&self) -> &str {
+        match self {
+            ArgumentDef::SingleValue(a) => &a.name,
+            ArgumentDef::MultiValue(a) => &a.name,
+        }
     }
 
-    pub fn new_multi_value(name: String) -> MultiValueArgumentDefBuilder {
-        MultiValueArgumentDefBuilder::new(name)
+    fn dash_values(&self) -> parser::DashValues {
+        match self {
+            ArgumentDef::SingleValue(a) => {
+                dash_values(a.allow_hyphen_values, a.allow_negative_numbers)
+            }
+            ArgumentDef::MultiValue(a) => {
+                dash_values(a.allow_hyphen_values, a.allow_negative_numbers)
+            }
+        }
     }
 
-    pub fn validate(
-        &self,
-        single_value_arguments: &IndexMap<String, String>,
-        multi_value_arguments: &IndexMap<String, Vec<String>>,
-    ) -> Result<(), String> {
+    pub fn get_help(&self) -> String {
+        let (name, help, defaults) = match self {
+            ArgumentDef::SingleValue(a) => (
+                &a.name,
+                &a.help,
+                a.default_value.as_ref().map(std::slice::from_ref),
+            ),
+            ArgumentDef::MultiValue(a) => (
+                &a.name,
+                &a.help,
+                Some(a.default_values.as_slice()).filter(|values| !values.is_empty()),
+            ),
+        };
+        let possible_values = self.possible_values();
+        let mut s = format!("    {}", name);
+        if !possible_values.is_empty() {
+            s.push_str(&format!(" {}", choices_part(possible_values)));
+        }
+        if let Some(help) = help {
+            s.push_str(&format!(" {}", help));
+        }
+        if let Some(values) = defaults {
+            s.push_str(&default_help(values));
+        }
+        s.push_str(&self.relations().1.help());
+        s.push('\n');
+        s.push_str(&choices_help(possible_values));
+        s
+    }
+
+    fn relations(&self) -> (&str, &Relations) {
         match self {
-            ArgumentDef::SingleValue(arg) => arg.validate(&single_value_arguments),
-            ArgumentDef::MultiValue(arg) => arg.validate(&multi_value_arguments),
+            ArgumentDef::SingleValue(a) => (&a.name, &a.relations),
+            ArgumentDef::MultiValue(a) => (&a.name, &a.relations),
         }
     }
-}
 
-impl From<SingleValueArgumentDef> for ArgumentDef {
-    fn from(item: SingleValueArgumentDef) -> Self {
-        ArgumentDef::SingleValue(item)
+    /// Values the argument accepts, empty if it accepts any.
+    pub fn possible_values(&self) -> &[PossibleValue] {
+        match self {
+            ArgumentDef::SingleValue(a) => &a.possible_values,
+            ArgumentDef::MultiValue(a) => &a.possible_values,
+        }
     }
-}
 
-impl From<MultiValueArgumentDef> for ArgumentDef {
-    fn from(item: MultiValueArgumentDef) -> Self {
-        ArgumentDef::MultiValue(item)
+    /// How values are compared with the possible values, `matching`
+    /// of the app unless the argument ignores case itself.
+    fn value_matching(&self, matching: Matching) -> Matching {
+        let ignore_case = match self {
+            ArgumentDef::SingleValue(a) => a.ignore_case,
+            ArgumentDef::MultiValue(a) => a.ignore_case,
+        };
+        Matching {
+            ignore_case: matching.ignore_case || ignore_case,
+            ..matching
+        }
     }
-}
 
-#[derive(Builder, Clone)]
-pub struct BasicSubCommandDef {
+    /// Values the argument gets when it was not given, if any.
+    fn default_values(&self, app: &App) -> Option<Vec<OsString>> {
+        let values = match self {
+            ArgumentDef::SingleValue(a) if !app.single_value_arguments.contains_key(&a.name) => {
+                pick_default(app, &a.defaults_if, a.default_value.as_ref())
+                    .map(std::slice::from_ref)
+            }
+            ArgumentDef::MultiValue(a) if !app.multi_value_arguments.contains_key(&a.name) => {
+                let default = Some(&a.default_values).filter(|values| !values.is_empty());
+                pick_default(app, &a.defaults_if, default).map(Vec::as_slice)
+            }
+            _ => None,
+        };
+        values.map(|values| values.iter().map(OsString::from).collect())
+    }
+
+    /// Whether the argument still lacks values it requires.
+    fn is_missing(
+        &self,
+        single_value_arguments: &IndexMap<String, OsString>,
+        multi_value_arguments: &IndexMap<String, Vec<OsString>>,
+    ) -> bool {
+        match self {
+            ArgumentDef::SingleValue(a) => {
+                a.required && !single_value_arguments.contains_key(&a.name)
+            }
+            ArgumentDef::MultiValue(a) => {
+                let cnt = multi_value_arguments.get(&a.name).map_or(0, Vec::len);
+                (cnt as u64) < a.min_occurences
+            }
+        }
+    }
+
+    pub fn new_single_value(name: String) -> SingleValueArgumentDefBuilder {
+        SingleValueArgumentDefBuilder::new(name)
+    }
+
+    pub fn new_multi_value(name: String) -> MultiValueArgumentDefBuilder {
+        MultiValueArgumentDefBuilder::new(name)
+    }
+
+    pub fn validate(
+        &self,
+        single_value_arguments: &IndexMap<String, OsString>,
+        multi_value_arguments: &IndexMap<String, Vec<OsString>>,
+    ) -> Result<(), String> {
+        match self {
+            ArgumentDef::SingleValue(arg) => arg.validate(single_value_arguments),
+            ArgumentDef::MultiValue(arg) => arg.validate(multi_value_arguments),
+        }
+    }
+}
+
+impl From<SingleValueArgumentDef> for ArgumentDef {
+    fn from(item: SingleValueArgumentDef) -> Self {
+        ArgumentDef::SingleValue(item)
+    }
+}
+
+impl From<MultiValueArgumentDef> for ArgumentDef {
+    fn from(item: MultiValueArgumentDef) -> Self {
+        ArgumentDef::MultiValue(item)
+    }
+}
+
+#[derive(Builder, Clone)]
+pub struct BasicSubCommandDef {
     name: String,
     #[builder(default = "None")]
     help: Option<String>,
@@ -716,28 +1375,103 @@ pub struct BasicSubCommandDef {
     validators: Option<Vec<SubCommandValidator>>,
     #[builder(default = "false")]
     required: bool,
-    #[builder(default = "vec![]")]
-    flags: Vec<FlagDef>,
+    #[builder(default = "FlagDefs(vec![])")]
+    flags: FlagDefs,
     #[builder(default = "None")]
+    #[allow(dead_code)]
     flag_groups: Option<IndexMap<String, Group>>,
     #[builder(default = "OptionDefs(vec![])")]
     options: OptionDefs,
     #[builder(default = "None")]
+    #[allow(dead_code)]
     option_groups: Option<IndexMap<String, Group>>,
     #[builder(default = "ArgumentDefs(vec![])")]
     arguments: ArgumentDefs,
+    #[builder(default = "None")]
     subcommand: Option<Box<SubCommandDef>>,
     #[builder(default = "None")]
+    #[allow(dead_code)]
     subcommand_groups: Option<IndexMap<String, Group>>,
+    /// how many times the subcommand may be given as a link of a chain
+    #[builder(default = "0")]
+    min_occurences: u64,
+    #[builder(default = "None")]
+    max_occurences: Option<u64>,
+    /// keep arguments after `--` unparsed, see `App::trailing`
+    #[builder(default = "false")]
+    trailing: bool,
+    /// order of options and positional arguments, the app's if not given
+    #[builder(default = "None")]
+    order: Option<ArgumentOrder>,
+}
+
+impl BasicSubCommandDef {
+    fn level(&self) -> Level<'_> {
+        Level {
+            flags: &self.flags,
+            options: &self.options,
+            arguments: &self.arguments,
+            subcommands: self.subcommand.as_deref().map_or(&[], std::slice::from_ref),
+            validators: self.validators.as_deref().unwrap_or(&[]),
+            occurrences: None,
+            trailing: self.trailing,
+            order: self.order,
+            root: false,
+        }
+    }
+}
+
+impl BasicSubCommandDefBuilder {
+    pub fn new(name: String) -> BasicSubCommandDefBuilder {
+        let mut bscdb = BasicSubCommandDefBuilder::default();
+        bscdb.name(name);
+        bscdb
+    }
+
+    pub fn add_flag(&mut self, flag_def: FlagDef) -> &mut Self {
+        match &mut self.flags {
+            Some(flag_defs) => {
+                flag_defs.add_flag(flag_def);
+            }
+            None => self.flags = Some(FlagDefs(vec![flag_def])),
+        };
+        self
+    }
+
+    pub fn add_option(&mut self, option_def: OptionDef) -> &mut Self {
+        match &mut self.options {
+            Some(v) => v.0.push(option_def),
+            None => self.options = Some(OptionDefs(vec![option_def])),
+        };
+        self
+    }
+
+    pub fn add_argument(&mut self, argument_def: ArgumentDef) -> &mut Self {
+        match &mut self.arguments {
+            Some(v) => v.0.push(argument_def),
+            None => self.arguments = Some(ArgumentDefs(vec![argument_def])),
+        };
+        self
+    }
+}
+
+impl From<BasicSubCommandDef> for SubCommandDef {
+    fn from(item: BasicSubCommandDef) -> Self {
+        SubCommandDef::BasicSubCommandDef(item)
+    }
 }
 
+/// Subcommand given one or more times in a row after the name of the chain,
+/// as `file` in `import file a file b`.
 #[derive(Builder, Clone)]
 pub struct SubCommandChainDef {
     name: String,
     #[builder(default = "None")]
     help: Option<String>,
+    #[builder(default = "vec![]")]
     validators: Vec<SubCommandValidator>,
-    arguments: Vec<ArgumentDef>,
+    #[builder(default = "ArgumentDefs(vec![])")]
+    arguments: ArgumentDefs,
     #[builder(default = "1")]
     min_occurences: u64,
     #[builder(default = "None")]
@@ -745,24 +1479,384 @@ pub struct SubCommandChainDef {
     subcommand: Box<SubCommandDef>,
 }
 
+impl SubCommandChainDef {
+    fn level(&self) -> Level<'_> {
+        Level {
+            flags: &NO_FLAGS,
+            options: &NO_OPTIONS,
+            arguments: &self.arguments,
+            subcommands: std::slice::from_ref(&self.subcommand),
+            validators: &self.validators,
+            occurrences: Some((self.min_occurences, self.max_occurences)),
+            trailing: false,
+            order: None,
+            root: false,
+        }
+    }
+}
+
+impl SubCommandChainDefBuilder {
+    pub fn new(name: String, subcommand: SubCommandDef) -> SubCommandChainDefBuilder {
+        let mut sccdb = SubCommandChainDefBuilder::default();
+        sccdb.name(name).subcommand(Box::new(subcommand));
+        sccdb
+    }
+
+    pub fn add_argument(&mut self, argument_def: ArgumentDef) -> &mut Self {
+        match &mut self.arguments {
+            Some(v) => v.0.push(argument_def),
+            None => self.arguments = Some(ArgumentDefs(vec![argument_def])),
+        };
+        self
+    }
+}
+
+impl From<SubCommandChainDef> for SubCommandDef {
+    fn from(item: SubCommandChainDef) -> Self {
+        SubCommandDef::SubCommandChainDef(item)
+    }
+}
+
+/// Different subcommands given in any order after the name of the chain,
+/// as `file` and `web` in `import file a web URL file b`.
 #[derive(Builder, Clone)]
 pub struct SubCommandChainsDef {
     name: String,
+    #[builder(default = "None")]
     help: Option<String>,
+    #[builder(default = "vec![]")]
     validators: Vec<SubCommandValidator>,
-    arguments: Vec<ArgumentDef>,
-    subcommands: Vec<Box<SubCommandDef>>,
+    #[builder(default = "ArgumentDefs(vec![])")]
+    arguments: ArgumentDefs,
+    #[builder(default = "vec![]")]
+    subcommands: Vec<SubCommandDef>,
+    #[builder(default = "1")]
+    min_occurences: u64,
+    #[builder(default = "None")]
+    max_occurences: Option<u64>,
+}
+
+impl SubCommandChainsDef {
+    fn level(&self) -> Level<'_> {
+        Level {
+            flags: &NO_FLAGS,
+            options: &NO_OPTIONS,
+            arguments: &self.arguments,
+            subcommands: &self.subcommands,
+            validators: &self.validators,
+            occurrences: Some((self.min_occurences, self.max_occurences)),
+            trailing: false,
+            order: None,
+            root: false,
+        }
+    }
+}
+
+impl SubCommandChainsDefBuilder {
+    pub fn new(name: String) -> SubCommandChainsDefBuilder {
+        let mut sccdb = SubCommandChainsDefBuilder::default();
+        sccdb.name(name);
+        sccdb
+    }
+
+    pub fn add_argument(&mut self, argument_def: ArgumentDef) -> &mut Self {
+        match &mut self.arguments {
+            Some(v) => v.0.push(argument_def),
+            None => self.arguments = Some(ArgumentDefs(vec![argument_def])),
+        };
+        self
+    }
+
+    pub fn add_subcommand(&mut self, subcommand: SubCommandDef) -> &mut Self {
+        match &mut self.subcommands {
+            Some(v) => v.push(subcommand),
+            None => self.subcommands = Some(vec![subcommand]),
+        };
+        self
+    }
+}
+
+impl From<SubCommandChainsDef> for SubCommandDef {
+    fn from(item: SubCommandChainsDef) -> Self {
+        SubCommandDef::SubCommandChainsDef(item)
+    }
+}
+
+/// Exactly one of several subcommands, as in `git commit|push|pull`.
+#[derive(Builder, Clone)]
+pub struct SubCommandEnumDef {
+    #[builder(default = "vec![]")]
+    subcommands: Vec<SubCommandDef>,
+    /// one of the subcommands must be given
+    #[builder(default = "false")]
+    required: bool,
+}
+
+impl SubCommandEnumDefBuilder {
+    pub fn new() -> SubCommandEnumDefBuilder {
+        SubCommandEnumDefBuilder::default()
+    }
+
+    pub fn add_subcommand(&mut self, subcommand: SubCommandDef) -> &mut Self {
+        match &mut self.subcommands {
+            Some(v) => v.push(subcommand),
+            None => self.subcommands = Some(vec![subcommand]),
+        };
+        self
+    }
+}
+
+impl From<SubCommandEnumDef> for SubCommandDef {
+    fn from(item: SubCommandEnumDef) -> Self {
+        SubCommandDef::SubCommandEnumDef(item)
+    }
 }
 
 #[derive(Clone)]
+#[allow(clippy::large_enum_variant)]
 pub enum SubCommandDef {
     BasicSubCommandDef(BasicSubCommandDef),
     SubCommandChainDef(SubCommandChainDef),
     SubCommandChainsDef(SubCommandChainsDef),
-    SubCommandEnumDef(Vec<Box<SubCommandDef>>),
+    SubCommandEnumDef(SubCommandEnumDef),
+}
+
+impl SubCommandDef {
+    /// Names that select this subcommand on the command line.
+    pub fn names(&self) -> Vec<&str> {
+        match self {
+            SubCommandDef::BasicSubCommandDef(bscd) => vec![bscd.name.as_str()],
+            SubCommandDef::SubCommandChainDef(sccd) => vec![sccd.name.as_str()],
+            SubCommandDef::SubCommandChainsDef(sccd) => vec![sccd.name.as_str()],
+            SubCommandDef::SubCommandEnumDef(scde) => scde
+                .subcommands
+                .iter()
+                .flat_map(|subcommand| subcommand.names())
+                .collect(),
+        }
+    }
+
+    /// Returns the subcommand selected by `name`.
+    fn find(&self, name: &str) -> Option<&SubCommandDef> {
+        match self {
+            SubCommandDef::BasicSubCommandDef(bscd) if bscd.name == name => Some(self),
+            SubCommandDef::SubCommandChainDef(sccd) if sccd.name == name => Some(self),
+            SubCommandDef::SubCommandChainsDef(sccd) if sccd.name == name => Some(self),
+            SubCommandDef::SubCommandEnumDef(scde) => scde
+                .subcommands
+                .iter()
+                .find_map(|subcommand| subcommand.find(name)),
+            _ => None,
+        }
+    }
+
+    /// Definitions of a subcommand returned by `find`.
+    fn level(&self) -> Level<'_> {
+        match self {
+            SubCommandDef::BasicSubCommandDef(bscd) => bscd.level(),
+            SubCommandDef::SubCommandChainDef(sccd) => sccd.level(),
+            SubCommandDef::SubCommandChainsDef(sccd) => sccd.level(),
+            SubCommandDef::SubCommandEnumDef(_) => {
+                unreachable!("subcommands are resolved to a member of the enum")
+            }
+        }
+    }
+
+    fn help(&self) -> Option<&str> {
+        match self {
+            SubCommandDef::BasicSubCommandDef(bscd) => bscd.help.as_deref(),
+            SubCommandDef::SubCommandChainDef(sccd) => sccd.help.as_deref(),
+            SubCommandDef::SubCommandChainsDef(sccd) => sccd.help.as_deref(),
+            SubCommandDef::SubCommandEnumDef(_) => None,
+        }
+    }
+
+    fn check_names(&self, matching: &Matching) -> Result<(), String> {
+        match self {
+            SubCommandDef::BasicSubCommandDef(bscd) => check_names(matching, bscd.level()),
+            SubCommandDef::SubCommandChainDef(sccd) => check_names(matching, sccd.level()),
+            SubCommandDef::SubCommandChainsDef(sccd) => check_names(matching, sccd.level()),
+            SubCommandDef::SubCommandEnumDef(scde) => {
+                for subcommand in &scde.subcommands {
+                    subcommand.check_names(matching)?;
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// Occurrence limits of the basic subcommands this stands for,
+    /// checked within chains.
+    fn limits(&self) -> Vec<(&str, u64, Option<u64>)> {
+        match self {
+            SubCommandDef::BasicSubCommandDef(bscd) => {
+                vec![(&bscd.name, bscd.min_occurences, bscd.max_occurences)]
+            }
+            SubCommandDef::SubCommandEnumDef(scde) => scde
+                .subcommands
+                .iter()
+                .flat_map(|subcommand| subcommand.limits())
+                .collect(),
+            _ => vec![],
+        }
+    }
+
+    fn is_required(&self) -> bool {
+        match self {
+            SubCommandDef::BasicSubCommandDef(bscd) => bscd.required,
+            SubCommandDef::SubCommandEnumDef(scde) => scde.required,
+            _ => false,
+        }
+    }
+}
+
+/// How names given on the command line are compared with long names,
+/// subcommand names and allowed values.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Matching {
+    /// `--Dry-Run` is the same as `--dry-run`
+    pub ignore_case: bool,
+    /// `-` and `_` are interchangeable, `--dry_run` is the same as `--dry-run`
+    pub ignore_separators: bool,
+}
+
+impl Matching {
+    pub fn normalize(&self, name: &str) -> String {
+        let mut normalized = if self.ignore_case {
+            name.to_lowercase()
+        } else {
+            name.to_string()
+        };
+        if self.ignore_separators {
+            normalized = normalized.replace('_', "-");
+        }
+        normalized
+    }
+
+    pub fn matches(&self, name: &str, candidate: &str) -> bool {
+        self.normalize(name) == self.normalize(candidate)
+    }
+
+    /// Finds the candidate `name` stands for. A full match always wins,
+    /// otherwise with `prefixes` an unambiguous prefix is accepted too,
+    /// an ambiguous one gives all candidates it matches as the error.
+    fn resolve<'a>(
+        &self,
+        name: &str,
+        candidates: &[&'a str],
+        prefixes: bool,
+    ) -> Result<Option<&'a str>, Vec<&'a str>> {
+        let name = self.normalize(name);
+        if let Some(exact) = candidates.iter().find(|c| self.normalize(c) == name) {
+            return Ok(Some(exact));
+        }
+        if !prefixes {
+            return Ok(None);
+        }
+        let matching: Vec<&str> = candidates
+            .iter()
+            .copied()
+            .filter(|c| self.normalize(c).starts_with(&name))
+            .collect();
+        match matching.len() {
+            0 => Ok(None),
+            1 => Ok(Some(matching[0])),
+            _ => Err(matching),
+        }
+    }
+
+    /// The candidate closest to `name`, if it is close enough to be what
+    /// was meant: one edit for every three characters is allowed.
+    fn suggest<'a>(&self, name: &str, candidates: &[&'a str]) -> Option<&'a str> {
+        let name = self.normalize(name);
+        let limit = (name.chars().count() / 3).max(1);
+        candidates
+            .iter()
+            .map(|candidate| (edit_distance(&name, &self.normalize(candidate)), *candidate))
+            .filter(|(distance, _)| *distance <= limit)
+            .min_by_key(|(distance, _)| *distance)
+            .map(|(_, candidate)| candidate)
+    }
+
+    /// Returns the first two `names` that are the same under this policy.
+    fn find_clash<'a>(&self, names: &[&'a str]) -> Option<(&'a str, &'a str)> {
+        for (idx, a) in names.iter().enumerate() {
+            for b in &names[idx + 1..] {
+                if self.matches(a, b) {
+                    return Some((a, b));
+                }
+            }
+        }
+        None
+    }
+}
+
+/// Number of characters to insert, delete or replace, or pairs of
+/// adjacent characters to swap, to turn `a` into `b`.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut rows = vec![(0..=b.len()).collect::<Vec<usize>>()];
+    for i in 1..=a.len() {
+        let mut row = vec![i; b.len() + 1];
+        for j in 1..=b.len() {
+            let cost = if a[i - 1] == b[j - 1] { 0 } else { 1 };
+            row[j] = (rows[i - 1][j] + 1)
+                .min(row[j - 1] + 1)
+                .min(rows[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                row[j] = row[j].min(rows[i - 2][j - 2] + 1);
+            }
+        }
+        rows.push(row);
+    }
+    rows[a.len()][b.len()]
+}
+
+/// `, did you mean --color?` for an error, when there is a suggestion.
+fn did_you_mean(suggestion: Option<String>) -> String {
+    match suggestion {
+        Some(suggestion) => format!(", did you mean {}?", suggestion),
+        None => String::new(),
+    }
+}
+
+/// Whether options may still be given after positional arguments.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum ArgumentOrder {
+    /// options and positional arguments may be mixed, as GNU getopt
+    /// permutes them: `rm file -f`
+    #[default]
+    Permute,
+    /// option parsing stops at the first positional argument and the rest
+    /// are values, as POSIX requires: `nice -n 5 make -j4`
+    Posix,
+    /// `Posix` if the `POSIXLY_CORRECT` environment variable is set,
+    /// `Permute` otherwise
+    PosixlyCorrect,
+}
+
+impl ArgumentOrder {
+    fn stops_at_positional(self, posixly_correct: bool) -> bool {
+        match self {
+            ArgumentOrder::Permute => false,
+            ArgumentOrder::Posix => true,
+            ArgumentOrder::PosixlyCorrect => posixly_correct,
+        }
+    }
+}
+
+/// Where the value of a flag, option or argument came from.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ValueSource {
+    CommandLine,
+    Environment,
+    Config,
+    Default,
 }
 
 #[derive(Builder, Clone, Default)]
+#[builder(build_fn(validate = "Self::validate_def"))]
 pub struct AppDef {
     name: String,
     #[builder(default = "env!(\"CARGO_PKG_VERSION\").to_string()")]
@@ -774,6 +1868,7 @@ pub struct AppDef {
     #[builder(default = "FlagDefs(vec![])")]
     flags: FlagDefs,
     #[builder(default = "None")]
+    #[allow(dead_code)]
     flag_groups: Option<IndexMap<String, Group>>,
     #[builder(default = "OptionDefs(vec![])")]
     options: OptionDefs,
@@ -783,56 +1878,333 @@ pub struct AppDef {
     arguments: ArgumentDefs,
     #[builder(default = "None")]
     subcommand: Option<Box<SubCommandDef>>,
+    #[builder(default = "Syntax::default()")]
+    syntax: Syntax,
+    /// expand `@file` arguments, see `response_file`
+    #[builder(default = "false")]
+    response_files: bool,
+    /// accept unambiguous prefixes of long flag, option and subcommand names
+    #[builder(default = "false")]
+    abbreviations: bool,
+    #[builder(default = "Matching::default()")]
+    matching: Matching,
+    /// keep arguments after `--` unparsed, see `App::trailing`
+    #[builder(default = "false")]
+    trailing: bool,
+    /// whether options may follow positional arguments, subcommands
+    /// may choose differently
+    #[builder(default = "ArgumentOrder::default()")]
+    order: ArgumentOrder,
+    /// variables to read instead of the process environment
+    #[builder(default = "None")]
+    environment: Option<IndexMap<String, String>>,
+    /// read values missing from the command line and the environment
+    /// from a configuration file, see `add_config_file`
+    #[cfg(feature = "config")]
+    #[builder(default = "false")]
+    config_file: bool,
+}
+
+/// Gives options and arguments of `level` that were not given their
+/// defaults. Conditional defaults only look at what was given.
+fn apply_defaults(level: Level, app: &mut App, streamed: Option<&str>) {
+    let options: Vec<_> = level
+        .options
+        .0
+        .iter()
+        .filter_map(|option_def| Some((option_def, option_def.default_values(app)?)))
+        .collect();
+    let arguments: Vec<_> = level
+        .arguments
+        .0
+        .iter()
+        .filter(|argument_def| streamed != Some(argument_def.name()))
+        .filter_map(|argument_def| Some((argument_def, argument_def.default_values(app)?)))
+        .collect();
+
+    for (option_def, mut values) in options {
+        app.sources.insert(option_def.name(), ValueSource::Default);
+        match option_def {
+            OptionDef::SingleValue(o) => {
+                if o.stdio && values[0] == STDIO {
+                    app.stdio_values.insert(o.name.clone());
+                }
+                app.single_value_options
+                    .insert(o.name.clone(), values.remove(0));
+            }
+            OptionDef::MultiValue(o) => {
+                app.multi_value_options.insert(o.name.clone(), values);
+            }
+        }
+    }
+    for (argument_def, mut values) in arguments {
+        app.sources
+            .insert(argument_def.name().to_string(), ValueSource::Default);
+        match argument_def {
+            ArgumentDef::SingleValue(a) => {
+                if a.stdio && values[0] == STDIO {
+                    app.stdio_values.insert(a.name.clone());
+                }
+                app.single_value_arguments
+                    .insert(a.name.clone(), values.remove(0));
+            }
+            ArgumentDef::MultiValue(a) => {
+                app.multi_value_arguments.insert(a.name.clone(), values);
+            }
+        }
+    }
+}
+
+/// Of two that override each other, drops the one given first.
+fn apply_overrides(level: Level, app: &mut App) {
+    for (name, relations) in level.relations() {
+        for other in &relations.overrides_with {
+            let position = |name: &str| app.given.get_full(name).map(|(idx, _)| idx);
+            if let (Some(this), Some(that)) = (position(name), position(other)) {
+                let earlier = if this < that { name } else { other };
+                app.remove(earlier);
+            }
+        }
+    }
+}
+
+/// Default values do not count as given.
+fn check_relations(level: Level, app: &App) -> Result<(), String> {
+    let given =
+        |name: &str| app.is_present(name) && app.source_of(name) != Some(ValueSource::Default);
+    for (name, relations) in level.relations() {
+        if given(name) {
+            if let Some(other) = relations.conflicts_with.iter().find(|other| given(other)) {
+                return Err(format!(
+                    "{} cannot be used with {}",
+                    level.describe(name),
+                    level.describe(other)
+                ));
+            }
+            if let Some(other) = relations
+                .requires
+                .iter()
+                .find(|other| !app.is_present(other))
+            {
+                return Err(format!(
+                    "{} requires {}",
+                    level.describe(name),
+                    level.describe(other)
+                ));
+            }
+        } else if !app.is_present(name) {
+            let unless = &relations.required_unless_present;
+            if !unless.is_empty() && !unless.iter().any(|other| given(other)) {
+                let others: Vec<String> =
+                    unless.iter().map(|other| level.describe(other)).collect();
+                return Err(format!(
+                    "{} is required unless {} is given",
+                    level.describe(name),
+                    others.join(" or ")
+                ));
+            }
+            if let Some((other, value)) = relations
+                .required_if
+                .iter()
+                .find(|(other, value)| given(other) && app.value_of(other) == Some(value))
+            {
+                return Err(format!(
+                    "{} is required when {} is {}",
+                    level.describe(name),
+                    level.describe(other),
+                    value
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+fn check_occurrences(name: &str, min: u64, max: Option<u64>, cnt: usize) -> Result<(), String> {
+    let cnt = cnt as u64;
+    if cnt < min {
+        return Err(format!(
+            "subcommand {} must appear at least {} time(s), it appeared {} time(s)",
+            name, min, cnt
+        ));
+    }
+    if let Some(max) = max {
+        if cnt > max {
+            return Err(format!(
+                "subcommand {} may appear at most {} time(s), it appeared {} time(s)",
+                name, max, cnt
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Definitions in effect at one level of the command line: the app
+/// itself or the subcommand being parsed.
+#[derive(Clone, Copy)]
+struct Level<'a> {
+    flags: &'a FlagDefs,
+    options: &'a OptionDefs,
+    arguments: &'a ArgumentDefs,
+    subcommands: &'a [SubCommandDef],
+    validators: &'a [AppValidator],
+    /// how many links a chain may have
+    occurrences: Option<(u64, Option<u64>)>,
+    /// collect values after `--` into `App::trailing`
+    trailing: bool,
+    /// `None` for levels that follow the app
+    order: Option<ArgumentOrder>,
+    /// the app itself, it takes the values of global flags and options
+    /// given in subcommands
+    root: bool,
+}
+
+impl<'a> Level<'a> {
+    fn subcommand_names(&self) -> Vec<&'a str> {
+        self.subcommands
+            .iter()
+            .flat_map(|subcommand| subcommand.names())
+            .collect()
+    }
+
+    fn relations(&self) -> impl Iterator<Item = (&'a str, &'a Relations)> {
+        let flags = self.flags.0.iter().map(FlagDef::relations);
+        let options = self.options.0.iter().map(OptionDef::relations);
+        let arguments = self.arguments.0.iter().map(ArgumentDef::relations);
+        flags.chain(options).chain(arguments)
+    }
+
+    /// `flag x`, `option x` or `argument x`, as errors name them.
+    fn describe(&self, name: &str) -> String {
+        let kind = if self.flags.0.iter().any(|fd| fd.name() == name) {
+            "flag"
+        } else if self.options.by_name(name).is_some() {
+            "option"
+        } else {
+            "argument"
+        };
+        format!("{} {}", kind, name)
+    }
+
+    fn defines(&self, name: &str) -> bool {
+        self.flags.0.iter().any(|fd| fd.name() == name)
+            || self.options.by_name(name).is_some()
+            || self.arguments.0.iter().any(|ad| ad.name() == name)
+    }
+}
+
+static NO_FLAGS: FlagDefs = FlagDefs(Vec::new());
+static NO_OPTIONS: OptionDefs = OptionDefs(Vec::new());
+static NO_ARGUMENTS: ArgumentDefs = ArgumentDefs(Vec::new());
+
+/// Why parsing of a level ended.
+enum Stop<'a> {
+    /// all arguments were read
+    End,
+    /// the next argument names a link of an enclosing chain
+    Link,
+    /// parsing reached a streamed argument
+    Stream(ValueStream<'a>),
+}
+
+/// What all levels share while one command line is parsed.
+struct Run<'a, 'r> {
+    tokens: &'r mut parser::TokenStream<'a>,
+    stream: bool,
+    diagnostics: &'r mut Diagnostics,
+    /// values of global flags and options given in subcommands,
+    /// merged into the top-level app when it is done
+    globals: App,
+    /// the configuration file, once the top level has read `--config`
+    #[cfg(feature = "config")]
+    config: Option<config::Config>,
+    /// names of the subcommands being parsed, they select the section
+    /// of the configuration file
+    #[cfg(feature = "config")]
+    section: Vec<String>,
+}
+
+impl Run<'_, '_> {
+    /// The app that takes the value of a flag or option found at a level
+    /// with `app`, `global` if it was not defined there but by the app.
+    fn target<'s>(&'s mut self, global: bool, app: &'s mut App) -> &'s mut App {
+        if global {
+            &mut self.globals
+        } else {
+            app
+        }
+    }
+
+    /// Drops the value attached to a flag or an unknown option, as in
+    /// `--force=yes`, so that it is not reported a second time.
+    fn skip_attached_value(&mut self, short: bool) -> bool {
+        if short && !matches!(self.tokens.peek(), Some(parser::Token::AttachedValue(_))) {
+            return false;
+        }
+        self.tokens.take_attached_value().is_some()
+    }
 }
 
 #[derive(Debug)]
+#[allow(clippy::enum_variant_names)]
 enum ArgumentParserState<'a> {
     ExpectAnything,
-    ExpectOptionValue(&'a OptionDef),
+    /// the option, and whether it is a global one given in a subcommand
+    ExpectOptionValue(&'a OptionDef, bool),
     ExpectArgumentValue(&'a ArgumentDef, usize),
     ExpectCommand,
     ExpectEnd,
 }
 
 impl AppDef {
-    pub fn get_arguments_part(&self) -> String {
-        let mut s = String::new();
-        for arg in &self.arguments.0 {
-            match arg {
-                ArgumentDef::SingleValue(real_arg) => {
-                    if real_arg.required {
-                        s.push_str(&format!(" {}", real_arg.name))
-                    } else {
-                        s.push_str(&format!(" [{}]", real_arg.name))
-                    }
-                }
-                ArgumentDef::MultiValue(real_arg) => {
-                    match (&real_arg.min_occurences, &real_arg.max_occurences) {
-                        (v1, None) => match v1 {
-                            0 => s.push_str(&format!(" [{}] [...]", real_arg.name)),
-                            _ => {
-                                for _ in 0..*v1 {
-                                    s.push_str(&format!(" {}", real_arg.name))
-                                }
-                                s.push_str(" [...]");
-                            }
-                        },
-                        (v1, Some(v2)) => {
-                            for _ in 0..*v1 {
-                                s.push_str(&format!(" {}", real_arg.name))
-                            }
+    fn level(&self) -> Level<'_> {
+        Level {
+            flags: &self.flags,
+            options: &self.options,
+            arguments: &self.arguments,
+            subcommands: self.subcommand.as_deref().map_or(&[], std::slice::from_ref),
+            validators: &self.validators,
+            occurrences: None,
+            trailing: self.trailing,
+            order: Some(self.order),
+            root: true,
+        }
+    }
 
-                            if v1 < v2 {
-                                for _ in *v1..*v2 {
-                                    s.push_str(&format!(" [{}]", real_arg.name))
-                                }
-                            }
-                        }
-                    }
-                }
-            }
+    /// Definitions and help of the subcommand reached through the names
+    /// in `path`, those of the app itself if `path` is empty.
+    fn subcommand_level(&self, path: &[&str]) -> Option<(Level<'_>, Option<&str>)> {
+        let mut level = self.level();
+        let mut help = self.help.as_deref();
+        for name in path {
+            let subcommand = level
+                .subcommands
+                .iter()
+                .find_map(|subcommand| subcommand.find(name))?;
+            level = subcommand.level();
+            help = subcommand.help();
         }
-        s
+        Some((level, help))
+    }
+
+    /// Values that option or argument `name` of the subcommand reached
+    /// through `path` accepts, for completion generators to offer. Empty
+    /// if it accepts any, `None` if there is no such option or argument.
+    pub fn possible_values(&self, path: &[&str], name: &str) -> Option<&[PossibleValue]> {
+        let (level, _) = self.subcommand_level(path)?;
+        if let Some((option_def, _)) = self.find_option(level, |options| options.by_name(name)) {
+            return Some(option_def.possible_values());
+        }
+        level
+            .arguments
+            .0
+            .iter()
+            .find(|argument_def| argument_def.name() == name)
+            .map(ArgumentDef::possible_values)
+    }
+
+    pub fn get_arguments_part(&self) -> String {
+        arguments_part(&self.arguments)
     }
 
     pub fn print_usage(&self) {
@@ -846,23 +2218,39 @@ impl AppDef {
         usage.push_str("USAGE:\n    ");
 
         usage.push_str(&self.name.clone());
-        if self.flags.0.len() > 0 {
+        if !self.flags.0.is_empty() {
             usage.push_str(" [FLAGS]");
         }
-        if self.options.0.len() > 0 {
+        if !self.options.0.is_empty() {
             usage.push_str(" [OPTIONS]");
         }
 
-        if self.arguments.0.len() > 0 {
+        if !self.arguments.0.is_empty() {
             usage.push_str(&self.get_arguments_part());
         }
 
-        if let Some(_) = &self.subcommand {
+        if self.subcommand.is_some() {
             usage.push_str(" [SUBCOMMAND(S)]");
         }
         usage.push_str("\n\n");
 
-        if self.options.0.len() > 0 {
+        if !self.arguments.0.is_empty() {
+            usage.push_str("ARGUMENTS:\n");
+            for argument_def in &self.arguments.0 {
+                usage.push_str(&argument_def.get_help());
+            }
+            usage.push('\n');
+        }
+
+        if !self.flags.0.is_empty() {
+            usage.push_str("FLAGS:\n");
+            for flag_def in &self.flags.0 {
+                usage.push_str(&flag_def.get_help());
+            }
+            usage.push('\n');
+        }
+
+        if !self.options.0.is_empty() {
             usage.push_str("OPTIONS:\n");
             let mut visited_options = IndexSet::new();
             if let Some(im) = &self.option_groups {
@@ -877,7 +2265,7 @@ impl AppDef {
                         Some(items) => {
                             for option_name in items {
                                 visited_options.insert(option_name);
-                                match self.options.by_name(&option_name) {
+                                match self.options.by_name(option_name) {
                                     Some(option_def) => {
                                         usage.push_str(&option_def.get_help());
                                     }
@@ -894,7 +2282,7 @@ impl AppDef {
                 }
             }
 
-            usage.push_str("\n");
+            usage.push('\n');
             for option_def in &self.options.0 {
                 if !visited_options.contains(&option_def.name()) {
                     usage.push_str(&option_def.get_help());
@@ -905,222 +2293,923 @@ impl AppDef {
         println!("{}", usage);
     }
 
+    /// Help for the subcommand reached through the names in `path`, such
+    /// as `["remote", "add"]`, global flags and options included.
+    pub fn get_subcommand_usage(&self, path: &[&str]) -> Option<String> {
+        let (level, help) = self.subcommand_level(path)?;
+        let mut command = self.name.clone();
+        for name in path {
+            command.push(' ');
+            command.push_str(name);
+        }
+
+        let (global_flags, global_options) = if level.root {
+            (vec![], vec![])
+        } else {
+            (
+                self.global_flags().collect(),
+                self.global_options().collect(),
+            )
+        };
+
+        let mut usage = String::new();
+        if let Some(help) = help {
+            usage.push_str(help);
+            usage.push_str("\n\n");
+        }
+        usage.push_str("USAGE:\n    ");
+        usage.push_str(&command);
+        if !level.flags.0.is_empty() || !global_flags.is_empty() {
+            usage.push_str(" [FLAGS]");
+        }
+        if !level.options.0.is_empty() || !global_options.is_empty() {
+            usage.push_str(" [OPTIONS]");
+        }
+        usage.push_str(&arguments_part(level.arguments));
+        if !level.subcommands.is_empty() {
+            usage.push_str(" [SUBCOMMAND(S)]");
+        }
+        usage.push('\n');
+
+        let sections: [(&str, Vec<String>); 5] = [
+            (
+                "ARGUMENTS",
+                level
+                    .arguments
+                    .0
+                    .iter()
+                    .map(ArgumentDef::get_help)
+                    .collect(),
+            ),
+            (
+                "FLAGS",
+                level.flags.0.iter().map(FlagDef::get_help).collect(),
+            ),
+            (
+                "OPTIONS",
+                level.options.0.iter().map(OptionDef::get_help).collect(),
+            ),
+            (
+                "GLOBAL FLAGS",
+                global_flags.iter().map(|fd| fd.get_help()).collect(),
+            ),
+            (
+                "GLOBAL OPTIONS",
+                global_options.iter().map(|od| od.get_help()).collect(),
+            ),
+        ];
+        for (title, lines) in &sections {
+            if !lines.is_empty() {
+                usage.push_str(&format!("\n{}:\n", title));
+                for line in lines {
+                    usage.push_str(line);
+                }
+            }
+        }
+        Some(usage)
+    }
+
     pub fn print_version(&self) {
         println!("{} {}", self.name, self.version);
     }
 
     pub fn parse_args(&self, args: &[&str]) -> Result<App, String> {
-        let mut app = App::new(&self.name);
+        self.parse_args_os(args)
+    }
+
+    pub fn parse_args_os<S: AsRef<OsStr>>(&self, args: &[S]) -> Result<App, String> {
+        let tokens = if self.response_files {
+            let expanded = response_file::expand(args).map_err(|e| e.to_string())?;
+            parser::TokenStream::with_syntax(expanded, self.syntax)
+        } else {
+            parser::TokenStream::with_syntax(args.iter(), self.syntax)
+        };
+        let (app, _) = self.parse_tokens(tokens, false, &mut Diagnostics::new(false))?;
+        Ok(app)
+    }
+
+    /// Parses a whole command line given as one string, splitting it into
+    /// arguments with POSIX shell quoting rules first.
+    pub fn parse_str(&self, line: &str) -> Result<App, String> {
+        let args = shell_words::split(line)
+            .map_err(|e| format!("{} at character {}", e.message, e.offset + 1))?;
+        self.parse_args_os(&args)
+    }
+
+    /// Parses arguments pulled lazily from `args`, such as `std::env::args_os()`.
+    ///
+    /// Parsing stops at the first value of a multi value argument marked
+    /// with `stream`, its values are then returned as a `ValueStream`
+    /// instead of being collected into `App`. A flag or option given
+    /// after that point is an error of the stream. Occurrence limits and
+    /// validators of a streamed argument are not checked. With response
+    /// files enabled all arguments are read before parsing starts.
+    pub fn parse_args_iter<'a, I, S>(&'a self, args: I) -> Result<(App, ValueStream<'a>), String>
+    where
+        I: IntoIterator<Item = S>,
+        I::IntoIter: 'a,
+        S: AsRef<OsStr>,
+    {
+        let tokens = if self.response_files {
+            let args: Vec<OsString> = args
+                .into_iter()
+                .map(|arg| arg.as_ref().to_os_string())
+                .collect();
+            let expanded = response_file::expand(&args).map_err(|e| e.to_string())?;
+            parser::TokenStream::with_syntax(expanded, self.syntax)
+        } else {
+            parser::TokenStream::with_syntax(args, self.syntax)
+        };
+        self.parse_tokens(tokens, true, &mut Diagnostics::new(false))
+    }
+
+    fn env_var(&self, name: &str) -> Option<OsString> {
+        match &self.environment {
+            Some(environment) => environment.get(name).map(OsString::from),
+            None => std::env::var_os(name),
+        }
+    }
 
+    /// Name and value of variable `env`, if there is one and it is set.
+    fn read_env<'e>(&self, env: &'e Option<String>) -> Option<(&'e str, OsString)> {
+        let env = env.as_deref()?;
+        Some((env, self.env_var(env)?))
+    }
+
+    /// Gives flags and options of `level` that were not given the value
+    /// of their environment variable, if it is set.
+    fn apply_env(&self, level: Level, app: &mut App) -> Result<(), String> {
+        for flag_def in &level.flags.0 {
+            let bfd = match flag_def {
+                FlagDef::BooleanFlagDef(bfd) if !app.boolean_flags.contains_key(&bfd.name) => bfd,
+                _ => continue,
+            };
+            if let Some((env, value)) = self.read_env(&bfd.env) {
+                let value = parse_bool(&value).ok_or_else(|| {
+                    format!(
+                        "flag {} expects a boolean in {}, got: {}",
+                        bfd.name,
+                        env,
+                        value.to_string_lossy()
+                    )
+                })?;
+                app.boolean_flags.insert(bfd.name.clone(), value);
+                app.sources
+                    .insert(bfd.name.clone(), ValueSource::Environment);
+            }
+        }
+        for option_def in &level.options.0 {
+            let (given, env) = match option_def {
+                OptionDef::SingleValue(o) => {
+                    (app.single_value_options.contains_key(&o.name), &o.env)
+                }
+                OptionDef::MultiValue(o) => (app.multi_value_options.contains_key(&o.name), &o.env),
+            };
+            let value = match self.read_env(env) {
+                Some((_, value)) if !given => value,
+                _ => continue,
+            };
+            // values that are not valid UTF-8 are never split
+            let values = match (option_def, value.to_str()) {
+                (OptionDef::MultiValue(o), Some(text)) => match o.env_delimiter {
+                    Some(delimiter) => text.split(delimiter).map(OsString::from).collect(),
+                    None => vec![value],
+                },
+                _ => vec![value],
+            };
+            for value in values {
+                app.add_option_value(option_def, &value)?;
+            }
+            app.sources
+                .insert(option_def.name(), ValueSource::Environment);
+        }
+        Ok(())
+    }
+
+    /// Checks values of options and arguments of `level` against their
+    /// possible values, each takes the spelling of the one it matches.
+    fn check_possible_values(&self, level: Level, app: &mut App) -> Result<(), String> {
+        for option_def in &level.options.0 {
+            let possible_values = option_def.possible_values();
+            let name = option_def.name();
+            let values = match option_def {
+                OptionDef::SingleValue(_) => app
+                    .single_value_options
+                    .get_mut(&name)
+                    .map(std::slice::from_mut),
+                OptionDef::MultiValue(_) => app
+                    .multi_value_options
+                    .get_mut(&name)
+                    .map(Vec::as_mut_slice),
+            };
+            let matching = option_def.value_matching(self.matching);
+            for value in values.unwrap_or_default() {
+                check_choice(&matching, "option", &name, possible_values, value)?;
+            }
+        }
+        for argument_def in &level.arguments.0 {
+            let possible_values = argument_def.possible_values();
+            let name = argument_def.name();
+            let values = match argument_def {
+                ArgumentDef::SingleValue(_) => app
+                    .single_value_arguments
+                    .get_mut(name)
+                    .map(std::slice::from_mut),
+                ArgumentDef::MultiValue(_) => app
+                    .multi_value_arguments
+                    .get_mut(name)
+                    .map(Vec::as_mut_slice),
+            };
+            let matching = argument_def.value_matching(self.matching);
+            for value in values.unwrap_or_default() {
+                check_choice(&matching, "argument", name, possible_values, value)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Looks a flag up among those of `level`, then among the global flags
+    /// of the app. The flag is global if it was not defined at `level`.
+    fn find_flag<'a>(
+        &'a self,
+        level: Level<'a>,
+        find: impl Fn(&'a FlagDefs) -> Option<&'a FlagDef>,
+    ) -> Option<(&'a FlagDef, bool)> {
+        match find(level.flags) {
+            Some(flag_def) => Some((flag_def, false)),
+            None if !level.root => find(&self.flags)
+                .filter(|flag_def| flag_def.is_global())
+                .map(|flag_def| (flag_def, true)),
+            None => None,
+        }
+    }
+
+    /// Like `find_flag`, for options.
+    fn find_option<'a>(
+        &'a self,
+        level: Level<'a>,
+        find: impl Fn(&'a OptionDefs) -> Option<&'a OptionDef>,
+    ) -> Option<(&'a OptionDef, bool)> {
+        match find(level.options) {
+            Some(option_def) => Some((option_def, false)),
+            None if !level.root => find(&self.options)
+                .filter(|option_def| option_def.is_global())
+                .map(|option_def| (option_def, true)),
+            None => None,
+        }
+    }
+
+    fn global_flags(&self) -> impl Iterator<Item = &FlagDef> {
+        self.flags.0.iter().filter(|flag_def| flag_def.is_global())
+    }
+
+    fn global_options(&self) -> impl Iterator<Item = &OptionDef> {
+        self.options
+            .0
+            .iter()
+            .filter(|option_def| option_def.is_global())
+    }
+
+    fn global_longs(&self, flags_only: bool) -> Vec<&str> {
+        let flags = self.global_flags().filter_map(|fd| match fd {
+            FlagDef::BooleanFlagDef(bfd) => bfd.long.as_deref(),
+            FlagDef::CountedFlagDef(cfd) => cfd.long.as_deref(),
+        });
+        let options = self
+            .global_options()
+            .filter(|_| !flags_only)
+            .filter_map(|od| match od {
+                OptionDef::SingleValue(svod) => svod.long.as_deref(),
+                OptionDef::MultiValue(mvod) => mvod.long.as_deref(),
+            });
+        flags.chain(options).collect()
+    }
+
+    /// Long names accepted at `level`, global ones included,
+    /// only those of flags when `flags_only`.
+    fn longs<'a>(&'a self, level: Level<'a>, flags_only: bool) -> Vec<&'a str> {
+        let mut longs = level.flags.longs();
+        if !flags_only {
+            longs.extend(level.options.longs());
+        }
+        if !level.root {
+            for long in self.global_longs(flags_only) {
+                if !longs.contains(&long) {
+                    longs.push(long);
+                }
+            }
+        }
+        longs
+    }
+
+    /// `, did you mean --color?` when `name` is close to a long name
+    /// accepted at `level`, given with `prefix`.
+    fn suggest_long(&self, level: Level, name: &str, prefix: &str, flags_only: bool) -> String {
+        let longs = self.longs(level, flags_only);
+        did_you_mean(
+            self.matching
+                .suggest(name, &longs)
+                .map(|long| format!("{}{}", prefix, long)),
+        )
+    }
+
+    /// With single-dash long names `-x` is read as long name `x`, it
+    /// stands for short name `x` when no long name is exactly `x`.
+    fn short_for_long(&self, level: Level, token: parser::Token) -> parser::Token {
+        let name = match &token {
+            parser::Token::Long(name) | parser::Token::PlusLong(name)
+                if self.syntax.single_dash_long =>
+            {
+                name
+            }
+            _ => return token,
+        };
+        let mut chars = name.chars();
+        let ch = match (chars.next(), chars.next()) {
+            (Some(ch), None) => ch,
+            _ => return token,
+        };
+        if let Ok(Some(_)) = self
+            .matching
+            .resolve(name, &self.longs(level, false), false)
+        {
+            return token;
+        }
+        match token {
+            parser::Token::PlusLong(_) => parser::Token::PlusShort(ch),
+            _ => parser::Token::Short(ch),
+        }
+    }
+
+    /// Whether `token` names a flag or option accepted at `level`.
+    fn is_known_option<'a>(&'a self, level: Level<'a>, token: &parser::Token) -> bool {
+        match token {
+            parser::Token::Short(ch) => {
+                self.find_flag(level, |f| f.by_short(ch)).is_some()
+                    || self.find_option(level, |o| o.by_short(ch)).is_some()
+            }
+            parser::Token::Long(name) => matches!(
+                self.matching
+                    .resolve(name, &self.longs(level, false), self.abbreviations),
+                Ok(Some(_))
+            ),
+            _ => false,
+        }
+    }
+
+    /// Returns the long flag or option name that `name` stands for,
+    /// recording it in `app` when it was abbreviated.
+    fn resolve_long<'a>(
+        &'a self,
+        level: Level<'a>,
+        name: &'a str,
+        app: &mut App,
+    ) -> Result<&'a str, String> {
+        let candidates = self.longs(level, false);
+        match self.matching.resolve(name, &candidates, self.abbreviations) {
+            Ok(Some(long)) => {
+                if !self.matching.matches(name, long) {
+                    app.abbreviations.insert(name.to_string(), long.to_string());
+                }
+                Ok(long)
+            }
+            Ok(None) => Ok(name),
+            Err(matching) => Err(format!(
+                "option --{} is ambiguous, it could be {}",
+                name,
+                matching
+                    .iter()
+                    .map(|long| format!("--{}", long))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )),
+        }
+    }
+
+    /// Returns the subcommand of `level` that `name` selects,
+    /// recording it in `app` when it was abbreviated.
+    fn resolve_subcommand<'a>(
+        &self,
+        level: Level<'a>,
+        name: &str,
+        app: &mut App,
+    ) -> Result<&'a SubCommandDef, String> {
+        let candidates = level.subcommand_names();
+        match self.matching.resolve(name, &candidates, self.abbreviations) {
+            Ok(Some(resolved)) => {
+                if !self.matching.matches(name, resolved) {
+                    app.abbreviations
+                        .insert(name.to_string(), resolved.to_string());
+                }
+                Ok(level
+                    .subcommands
+                    .iter()
+                    .find_map(|subcommand| subcommand.find(resolved))
+                    .unwrap())
+            }
+            Ok(None) => match self.matching.suggest(name, &candidates) {
+                Some(suggestion) => Err(format!(
+                    "unknown subcommand: {}, did you mean {}?",
+                    name, suggestion
+                )),
+                None => Err(format!(
+                    "unknown subcommand: {}, expected one of: {}",
+                    name,
+                    candidates.join(", ")
+                )),
+            },
+            Err(matching) => Err(format!(
+                "subcommand {} is ambiguous, it could be {}",
+                name,
+                matching.join(", ")
+            )),
+        }
+    }
+
+    /// Parses `args` without stopping at the first problem: bad arguments
+    /// are skipped and every error is returned together with whatever could
+    /// be parsed.
+    pub fn parse_args_recover(&self, args: &[&str]) -> (App, Vec<String>) {
+        self.parse_args_os_recover(args)
+    }
+
+    pub fn parse_args_os_recover<S: AsRef<OsStr>>(&self, args: &[S]) -> (App, Vec<String>) {
+        let tokens = if self.response_files {
+            match response_file::expand(args) {
+                Ok(expanded) => parser::TokenStream::with_syntax(expanded, self.syntax),
+                Err(e) => return (App::new(&self.name), vec![e.to_string()]),
+            }
+        } else {
+            parser::TokenStream::with_syntax(args.iter(), self.syntax)
+        };
+        let mut diagnostics = Diagnostics::new(true);
+        match self.parse_tokens(tokens, false, &mut diagnostics) {
+            Ok((app, _)) => (app, diagnostics.errors),
+            Err(_) => unreachable!("errors are collected when recovering"),
+        }
+    }
+
+    fn parse_tokens<'a>(
+        &'a self,
+        mut tokens: parser::TokenStream<'a>,
+        stream: bool,
+        diagnostics: &mut Diagnostics,
+    ) -> Result<(App, ValueStream<'a>), String> {
+        let mut run = Run {
+            tokens: &mut tokens,
+            stream,
+            diagnostics,
+            globals: App::new(&self.name),
+            #[cfg(feature = "config")]
+            config: None,
+            #[cfg(feature = "config")]
+            section: vec![],
+        };
+        let app = App::new(&self.name);
+        let (app, stop) = self.parse_level(self.level(), app, &[], &mut run)?;
+        let values = match stop {
+            Stop::Stream(values) => values,
+            _ => ValueStream::new(None, std::iter::empty()),
+        };
+        Ok((app, values))
+    }
+
+    /// Parses the arguments that belong to `level` into `app`, descending
+    /// into a subcommand when one is given. A value naming one of the
+    /// `links` of an enclosing chain ends the level and is left unread.
+    fn parse_level<'a>(
+        &'a self,
+        level: Level<'a>,
+        mut app: App,
+        links: &[&'a str],
+        run: &mut Run<'a, '_>,
+    ) -> Result<(App, Stop<'a>), String> {
         let mut state = ArgumentParserState::ExpectAnything;
-        let parser = parser::Parser::new(&args);
-
-        for token in parser.iter() {
-            match (&mut state, &token) {
-                (ArgumentParserState::ExpectAnything, parser::Token::Short(ch)) => {
-                    match self.flags.by_short(ch) {
-                        Some(ref flag_def) => match flag_def {
-                            FlagDef::BooleanFlagDef(bf) => {
-                                match app.boolean_flags.get(&bf.name) {
-                                    Some(_) => return Err("Invalid arguments".to_string()),
-                                    None => {
-                                        app.boolean_flags.insert(bf.name.clone(), true);
-                                    }
-                                };
-                                state = ArgumentParserState::ExpectAnything;
-                            }
-                            FlagDef::CountedFlagDef(cf) => {
-                                match app.counted_flags.entry(cf.name.clone()) {
-                                    Entry::Occupied(mut value) => {
-                                        let v = value.get_mut();
-                                        *v += 1;
-                                    }
-                                    Entry::Vacant(entry) => {
-                                        entry.insert(1);
-                                    }
-                                }
-                                state = ArgumentParserState::ExpectAnything;
+        // positional state to go back to once an option has its value
+        let mut resume = ArgumentParserState::ExpectAnything;
+        // after `--` values only fill positional arguments
+        let mut separated = false;
+        let posix = level
+            .order
+            .unwrap_or(self.order)
+            .stops_at_positional(self.env_var("POSIXLY_CORRECT").is_some());
+
+        loop {
+            let (mut dash_values, started) = match &state {
+                ArgumentParserState::ExpectOptionValue(option_def, _) => {
+                    (option_def.dash_values(), true)
+                }
+                ArgumentParserState::ExpectAnything => match level.arguments.0.first() {
+                    Some(arg_def) => (arg_def.dash_values(), false),
+                    None => (parser::DashValues::None, false),
+                },
+                ArgumentParserState::ExpectArgumentValue(arg_def, _) => {
+                    (arg_def.dash_values(), app.is_present(arg_def.name()))
+                }
+                _ => (parser::DashValues::None, false),
+            };
+            // until a positional has a value, flags and options
+            // that are known win over taking the argument as its value
+            if dash_values != parser::DashValues::None && !started {
+                if let Some(token) = run.tokens.peek() {
+                    if self.is_known_option(level, &self.short_for_long(level, token)) {
+                        dash_values = parser::DashValues::None;
+                    }
+                }
+            }
+            // when an option value is missing, the token is read again
+            // as if the option was not there
+            let checkpoint = match state {
+                ArgumentParserState::ExpectOptionValue(..) if run.diagnostics.recover => {
+                    Some(run.tokens.checkpoint())
+                }
+                _ if !links.is_empty() => Some(run.tokens.checkpoint()),
+                _ => None,
+            };
+            let operands = posix
+                && !matches!(
+                    state,
+                    ArgumentParserState::ExpectAnything
+                        | ArgumentParserState::ExpectOptionValue(..)
+                );
+            let token = if operands {
+                run.tokens.next_operand()
+            } else {
+                run.tokens.next_with(dash_values)
+            };
+            let token = match token {
+                Some(token) => self.short_for_long(level, token),
+                None => break,
+            };
+            if let parser::Token::Value(value) = &token {
+                if !separated && self.ends_level(level, &state, &app, links, value) {
+                    if let Some(checkpoint) = checkpoint {
+                        run.tokens.restore(&checkpoint);
+                        run.tokens.release(checkpoint);
+                    }
+                    self.validate(level, &mut app, None, run)?;
+                    return Ok((app, Stop::Link));
+                }
+            }
+            // past this point only a missing option value goes back, so
+            // the checkpoint is not kept while a subcommand or a stream
+            // reads on, or its argument could never be dropped
+            let checkpoint = match checkpoint {
+                Some(checkpoint)
+                    if !matches!(state, ArgumentParserState::ExpectOptionValue(..)) =>
+                {
+                    run.tokens.release(checkpoint);
+                    None
+                }
+                checkpoint => checkpoint,
+            };
+            let result = match (&mut state, &token) {
+                (_, parser::Token::Error(message, position)) => {
+                    run.tokens.recover();
+                    Err(format!(
+                        "{} (argument {}, character {})",
+                        message,
+                        position.argument() + 1,
+                        position.char_in_argument() + 1
+                    ))
+                }
+                (
+                    ArgumentParserState::ExpectOptionValue(option_def, global),
+                    parser::Token::Value(value),
+                ) => {
+                    let result = run
+                        .target(*global, &mut app)
+                        .add_option_value(option_def, value);
+                    state = std::mem::replace(&mut resume, ArgumentParserState::ExpectAnything);
+                    result
+                }
+                (ArgumentParserState::ExpectOptionValue(option_def, _), _) => {
+                    let error = format!("option {} requires a value", option_def.name());
+                    if let Some(checkpoint) = &checkpoint {
+                        run.tokens.restore(checkpoint);
+                        state = std::mem::replace(&mut resume, ArgumentParserState::ExpectAnything);
+                    }
+                    Err(error)
+                }
+
+                // options may also follow positional arguments, unless
+                // `posix` made them values above
+                (_, parser::Token::Short(ch)) => match self.find_flag(level, |f| f.by_short(ch)) {
+                    Some((flag_def, _)) if run.skip_attached_value(true) => {
+                        Err(format!("flag {} does not take a value", flag_def.name()))
+                    }
+                    Some((flag_def, global)) => run.target(global, &mut app).set_flag(flag_def),
+                    None => match self.find_option(level, |o| o.by_short(ch)) {
+                        Some((option_def, global)) => match run.tokens.take_attached_value() {
+                            Some(value) => run
+                                .target(global, &mut app)
+                                .add_option_value(option_def, &value),
+                            None => {
+                                resume = std::mem::replace(
+                                    &mut state,
+                                    ArgumentParserState::ExpectOptionValue(option_def, global),
+                                );
+                                Ok(())
                             }
                         },
-                        None => match self.options.by_short(ch) {
-                            Some(ref option_def) => {
-                                state = ArgumentParserState::ExpectOptionValue(option_def);
+                        None => {
+                            run.skip_attached_value(true);
+                            Err(format!("unknown option: -{}", ch))
+                        }
+                    },
+                },
+                (_, parser::Token::Long(param)) => {
+                    match self.resolve_long(level, param, &mut app) {
+                        Err(e) => {
+                            run.skip_attached_value(false);
+                            Err(e)
+                        }
+                        Ok(param) => match self.find_flag(level, |f| f.by_long(param)) {
+                            Some((flag_def, _)) if run.skip_attached_value(false) => {
+                                Err(format!("flag {} does not take a value", flag_def.name()))
                             }
-                            None => return Err("Invalid arguments".to_string()),
+                            Some((flag_def, global)) => {
+                                run.target(global, &mut app).set_flag(flag_def)
+                            }
+                            None => match self.find_option(level, |o| o.by_long(param)) {
+                                Some((option_def, global)) => {
+                                    match run.tokens.take_attached_value() {
+                                        Some(value) => run
+                                            .target(global, &mut app)
+                                            .add_option_value(option_def, &value),
+                                        None => {
+                                            resume = std::mem::replace(
+                                                &mut state,
+                                                ArgumentParserState::ExpectOptionValue(
+                                                    option_def, global,
+                                                ),
+                                            );
+                                            Ok(())
+                                        }
+                                    }
+                                }
+                                None => {
+                                    run.skip_attached_value(false);
+                                    Err(format!(
+                                        "unknown option: --{}{}",
+                                        param,
+                                        self.suggest_long(level, param, "--", false)
+                                    ))
+                                }
+                            },
                         },
                     }
                 }
-                (ArgumentParserState::ExpectAnything, parser::Token::Long(param)) => {
-                    match self.flags.by_long(param) {
-                        Some(ref flag_def) => match flag_def {
-                            FlagDef::BooleanFlagDef(bf) => {
-                                match app.boolean_flags.get(&bf.name) {
-                                    Some(_) => return Err("Invalid arguments".to_string()),
-                                    None => {
-                                        app.boolean_flags.insert(bf.name.clone(), true);
-                                    }
-                                };
-                                state = ArgumentParserState::ExpectAnything;
+                (_, parser::Token::PlusShort(ch)) => {
+                    match self.find_flag(level, |f| f.by_short(ch)) {
+                        Some((flag_def, _)) if run.skip_attached_value(true) => {
+                            Err(format!("flag {} does not take a value", flag_def.name()))
+                        }
+                        Some((flag_def, global)) => {
+                            run.target(global, &mut app).unset_flag(flag_def)
+                        }
+                        None => {
+                            run.skip_attached_value(true);
+                            Err(format!("unknown flag: +{}", ch))
+                        }
+                    }
+                }
+                (_, parser::Token::PlusLong(param)) => {
+                    match self.resolve_long(level, param, &mut app) {
+                        Err(e) => {
+                            run.skip_attached_value(false);
+                            Err(e)
+                        }
+                        Ok(param) => match self.find_flag(level, |f| f.by_long(param)) {
+                            Some((flag_def, _)) if run.skip_attached_value(false) => {
+                                Err(format!("flag {} does not take a value", flag_def.name()))
                             }
-                            FlagDef::CountedFlagDef(cf) => {
-                                match app.counted_flags.entry(cf.name.clone()) {
-                                    Entry::Occupied(mut value) => {
-                                        let v = value.get_mut();
-                                        *v += 1;
-                                    }
-                                    Entry::Vacant(entry) => {
-                                        entry.insert(1);
-                                    }
-                                }
-                                state = ArgumentParserState::ExpectAnything;
+                            Some((flag_def, global)) => {
+                                run.target(global, &mut app).unset_flag(flag_def)
                             }
-                        },
-                        None => match self.options.by_long(param) {
-                            Some(ref option_def) => {
-                                state = ArgumentParserState::ExpectOptionValue(option_def);
+                            None => {
+                                run.skip_attached_value(false);
+                                Err(format!(
+                                    "unknown flag: +{}{}",
+                                    param,
+                                    self.suggest_long(level, param, "+", true)
+                                ))
                             }
-                            None => return Err("Invalid arguments".to_string()),
                         },
                     }
                 }
+                (_, parser::Token::AttachedValue(value)) => {
+                    Err(format!("unexpected value: {}", value.to_string_lossy()))
+                }
 
-                (
-                    ArgumentParserState::ExpectOptionValue(ref option_def),
-                    parser::Token::Value(value),
-                ) => match option_def {
-                    OptionDef::SingleValue(svod) => {
-                        match app.single_value_arguments.get(&svod.name) {
-                            Some(_) => return Err("Invalid arguments".to_string()),
-                            None => {
-                                app.single_value_arguments
-                                    .insert(svod.name.clone(), value.to_string());
-                                state = ArgumentParserState::ExpectAnything;
-                            }
+                (_, parser::Token::Value(value)) if separated && level.trailing => {
+                    app.trailing.push(value.clone());
+                    Ok(())
+                }
+                (_, parser::Token::PositionalSeparator) => {
+                    separated = true;
+                    Ok(())
+                }
+                (ArgumentParserState::ExpectAnything, parser::Token::Value(value))
+                | (ArgumentParserState::ExpectArgumentValue(..), parser::Token::Value(value)) => {
+                    let arg_idx = match state {
+                        ArgumentParserState::ExpectArgumentValue(_, arg_idx) => arg_idx,
+                        _ => 0,
+                    };
+                    match level.arguments.0.get(arg_idx) {
+                        Some(ArgumentDef::MultiValue(mvad)) if run.stream && mvad.stream => {
+                            self.validate(level, &mut app, Some(&mvad.name), run)?;
+                            let tokens = std::mem::replace(
+                                run.tokens,
+                                parser::TokenStream::new(Vec::<OsString>::new()),
+                            );
+                            let dash_values = level.arguments.0[arg_idx].dash_values();
+                            let values = stream_values(&mvad.name, tokens, posix, dash_values);
+                            let values = std::iter::once(Ok(value.clone())).chain(values);
+                            let values = ValueStream::new(Some(mvad.name.clone()), values);
+                            return Ok((app, Stop::Stream(values)));
                         }
-                    }
-                    OptionDef::MultiValue(mvod) => {
-                        match app.multi_value_arguments.entry(mvod.name.clone()) {
-                            Entry::Occupied(mut entry) => {
-                                let v = entry.get_mut();
-                                v.push(value.to_string());
-                            }
-                            Entry::Vacant(entry) => {
-                                entry.insert(vec![value.to_string()]);
-                            }
+                        Some(_) => {
+                            state = self.add_argument_value(level, &mut app, arg_idx, value);
+                            Ok(())
                         }
-
-                        state = ArgumentParserState::ExpectAnything;
-                    }
-                },
-
-                (ArgumentParserState::ExpectAnything, parser::Token::Value(value)) => {
-                    if self.arguments.0.len() > 0 {
-                        let arg_def = &self.arguments.0[0];
-                        match arg_def {
-                            ArgumentDef::SingleValue(svad) => {
-                                app.single_value_arguments
-                                    .insert(svad.name.clone(), value.to_string());
-                                if self.arguments.0.len() == 1 {
-                                    if self.subcommand.is_some() {
-                                        state = ArgumentParserState::ExpectCommand;
-                                    } else {
-                                        state = ArgumentParserState::ExpectEnd;
+                        None if !separated && !level.subcommands.is_empty() => {
+                            match self.resolve_subcommand(level, &value.to_string_lossy(), &mut app)
+                            {
+                                Ok(subcommand) => {
+                                    #[cfg(feature = "config")]
+                                    self.load_config(level, &app, run)?;
+                                    match self.parse_subcommand(level, subcommand, links, run)? {
+                                        (sub_app, Stop::Link) if level.occurrences.is_some() => {
+                                            app.subcommands.push(sub_app);
+                                            state = ArgumentParserState::ExpectCommand;
+                                            Ok(())
+                                        }
+                                        (sub_app, stop) => {
+                                            app.subcommands.push(sub_app);
+                                            self.validate(level, &mut app, None, run)?;
+                                            return Ok((app, stop));
+                                        }
                                     }
-                                } else {
-                                    state = ArgumentParserState::ExpectArgumentValue(
-                                        &self.arguments.0[1],
-                                        1,
-                                    );
-                                }
-                            }
-                            ArgumentDef::MultiValue(mvad) => {
-                                app.multi_value_arguments
-                                    .insert(mvad.name.clone(), vec![value.to_string()]);
-
-                                if mvad.max_occurences == Some(1) {
-                                    if self.subcommand.is_some() {
-                                        state = ArgumentParserState::ExpectCommand;
-                                    } else {
-                                        state = ArgumentParserState::ExpectEnd;
-                                    }
-                                } else {
-                                    state = ArgumentParserState::ExpectArgumentValue(
-                                        &self.arguments.0[1],
-                                        1,
-                                    );
                                 }
+                                Err(e) => Err(e),
                             }
                         }
+                        None => Err(format!("unexpected argument: {}", value.to_string_lossy())),
                     }
                 }
-
-                (
-                    ArgumentParserState::ExpectArgumentValue(arg_def, arg_idx),
-                    parser::Token::Value(value),
-                ) => match arg_def {
-                    ArgumentDef::SingleValue(svad) => {
-                        app.single_value_arguments
-                            .insert(svad.name.clone(), value.to_string());
-                        if *arg_idx + 1 == self.arguments.0.len() {
-                            if self.subcommand.is_some() {
-                                state = ArgumentParserState::ExpectCommand;
-                            } else {
-                                state = ArgumentParserState::ExpectEnd;
-                            }
-                        } else {
-                            state = ArgumentParserState::ExpectArgumentValue(
-                                &self.arguments.0[*arg_idx + 1],
-                                *arg_idx + 1,
-                            );
-                        }
-                    }
-                    ArgumentDef::MultiValue(mvad) => {
-                        let value_cnt = match app.multi_value_arguments.entry(mvad.name.clone()) {
-                            Entry::Occupied(mut entry) => {
-                                let values = entry.get_mut();
-                                values.push(value.to_string());
-                                values.len()
-                            }
-                            Entry::Vacant(entry) => {
-                                entry.insert(vec![value.to_string()]);
-                                1
-                            }
-                        };
-                        if let Some(max) = mvad.max_occurences {
-                            if value_cnt == max as usize {
-                                if *arg_idx + 1 == self.arguments.0.len() {
-                                    if self.subcommand.is_some() {
-                                        state = ArgumentParserState::ExpectCommand;
-                                    } else {
-                                        state = ArgumentParserState::ExpectEnd;
-                                    }
-                                } else {
-                                    state = ArgumentParserState::ExpectArgumentValue(
-                                        &self.arguments.0[*arg_idx + 1],
-                                        *arg_idx + 1,
-                                    );
+                (ArgumentParserState::ExpectCommand, parser::Token::Value(value)) if !separated => {
+                    match self.resolve_subcommand(level, &value.to_string_lossy(), &mut app) {
+                        Ok(subcommand) => {
+                            #[cfg(feature = "config")]
+                            self.load_config(level, &app, run)?;
+                            match self.parse_subcommand(level, subcommand, links, run)? {
+                                (sub_app, Stop::Link) if level.occurrences.is_some() => {
+                                    app.subcommands.push(sub_app);
+                                    Ok(())
+                                }
+                                (sub_app, stop) => {
+                                    app.subcommands.push(sub_app);
+                                    self.validate(level, &mut app, None, run)?;
+                                    return Ok((app, stop));
                                 }
                             }
                         }
+                        Err(e) => Err(e),
                     }
-                },
+                }
+                (ArgumentParserState::ExpectCommand, parser::Token::Value(value))
+                | (ArgumentParserState::ExpectEnd, parser::Token::Value(value)) => {
+                    Err(format!("unexpected argument: {}", value.to_string_lossy()))
+                }
                 (ArgumentParserState::ExpectAnything, parser::Token::End)
+                | (ArgumentParserState::ExpectArgumentValue(..), parser::Token::End)
+                | (ArgumentParserState::ExpectCommand, parser::Token::End)
                 | (ArgumentParserState::ExpectEnd, parser::Token::End) => {
-                    //TODO: validate arguments
-                    self.validate(&app)?;
-                    return Ok(app);
-                }
-                (state, token) => {
-                    return Err(format!("Invalid arguments: {:?}, {:?}", state, token))
+                    self.validate(level, &mut app, None, run)?;
+                    return Ok((app, Stop::End));
                 }
+            };
+            if let Some(checkpoint) = checkpoint {
+                run.tokens.release(checkpoint);
+            }
+            run.diagnostics.report(result)?;
+        }
+
+        Ok((app, Stop::End))
+    }
+
+    /// Whether `value` names a link of an enclosing chain and the current
+    /// level can end before it, its own subcommands taking precedence.
+    /// With abbreviations a prefix names a link as well.
+    fn ends_level(
+        &self,
+        level: Level,
+        state: &ArgumentParserState,
+        app: &App,
+        links: &[&str],
+        value: &OsStr,
+    ) -> bool {
+        let name = value.to_string_lossy();
+        // a prefix of several names still counts, the ambiguity is
+        // reported where the name is resolved
+        let names_link = |names: &[&str]| {
+            !matches!(
+                self.matching.resolve(&name, names, self.abbreviations),
+                Ok(None)
+            )
+        };
+        if !names_link(links) {
+            return false;
+        }
+        if names_link(&level.subcommand_names()) {
+            return false;
+        }
+        let pending = match state {
+            ArgumentParserState::ExpectOptionValue(..) => return false,
+            ArgumentParserState::ExpectAnything => level.arguments.0.first(),
+            ArgumentParserState::ExpectArgumentValue(arg_def, _) => Some(*arg_def),
+            _ => None,
+        };
+        match pending {
+            Some(arg_def) => {
+                !arg_def.is_missing(&app.single_value_arguments, &app.multi_value_arguments)
             }
+            None => true,
+        }
+    }
+
+    /// Parses the arguments that follow the name of `subcommand`,
+    /// a subcommand of `level`.
+    fn parse_subcommand<'a>(
+        &'a self,
+        level: Level<'a>,
+        subcommand: &'a SubCommandDef,
+        links: &[&'a str],
+        run: &mut Run<'a, '_>,
+    ) -> Result<(App, Stop<'a>), String> {
+        let mut links = links.to_vec();
+        if level.occurrences.is_some() {
+            links.extend(level.subcommand_names());
         }
+        #[cfg(feature = "config")]
+        run.section.push(subcommand.names()[0].to_string());
+        let parsed = match subcommand {
+            SubCommandDef::BasicSubCommandDef(bscd) => {
+                self.parse_level(bscd.level(), App::new(&bscd.name), &links, run)
+            }
+            SubCommandDef::SubCommandChainDef(sccd) => {
+                self.parse_level(sccd.level(), App::new(&sccd.name), &links, run)
+            }
+            SubCommandDef::SubCommandChainsDef(sccd) => {
+                self.parse_level(sccd.level(), App::new(&sccd.name), &links, run)
+            }
+            SubCommandDef::SubCommandEnumDef(_) => {
+                unreachable!("subcommands are resolved to a member of the enum")
+            }
+        };
+        #[cfg(feature = "config")]
+        run.section.pop();
+        parsed
+    }
 
-        Err("err".to_string())
+    /// Stores `value` as a value of the positional argument at `arg_idx`
+    /// and returns the state to continue in.
+    fn add_argument_value<'a>(
+        &self,
+        level: Level<'a>,
+        app: &mut App,
+        arg_idx: usize,
+        value: &OsStr,
+    ) -> ArgumentParserState<'a> {
+        let arg_def = &level.arguments.0[arg_idx];
+        match arg_def {
+            ArgumentDef::SingleValue(svad) => app.set_argument_value(svad, value),
+            ArgumentDef::MultiValue(mvad) => {
+                app.record_given(&mvad.name);
+                let values = app
+                    .multi_value_arguments
+                    .entry(mvad.name.clone())
+                    .or_default();
+                values.push(value.to_os_string());
+                if mvad.max_occurences != Some(values.len() as u64) {
+                    return ArgumentParserState::ExpectArgumentValue(arg_def, arg_idx);
+                }
+            }
+        }
+        match level.arguments.0.get(arg_idx + 1) {
+            Some(next_def) => ArgumentParserState::ExpectArgumentValue(next_def, arg_idx + 1),
+            None if !level.subcommands.is_empty() => ArgumentParserState::ExpectCommand,
+            None => ArgumentParserState::ExpectEnd,
+        }
     }
 
     pub fn from_args(&self, args: &[&str]) -> Result<App, String> {
-        match self.parse_args(&args) {
+        self.from_args_os(args)
+    }
+
+    pub fn from_args_os<S: AsRef<OsStr>>(&self, args: &[S]) -> Result<App, String> {
+        match self.parse_args_os(args) {
             Ok(app) => Ok(app),
             Err(s) => {
                 eprintln!("{}", s);
@@ -1129,31 +3218,410 @@ impl AppDef {
         }
     }
 
-    fn validate(&self, app: &App) -> Result<(), String> {
-        self.flags
-            .validate(&app.boolean_flags, &app.counted_flags)?;
-        self.options
-            .validate(&app.single_value_options, &app.multi_value_options)?;
-        self.arguments
-            .validate(&app.single_value_arguments, &app.multi_value_arguments)?;
+    /// Checks the parsed values, skipping the `streamed` argument
+    /// whose values were not collected.
+    fn validate(
+        &self,
+        level: Level,
+        app: &mut App,
+        streamed: Option<&str>,
+        run: &mut Run,
+    ) -> Result<(), String> {
+        if level.root {
+            let globals = std::mem::replace(&mut run.globals, App::new(&self.name));
+            run.diagnostics.report(app.merge_globals(globals))?;
+        }
+        apply_overrides(level, app);
+        // loaded from the command line alone, as when a subcommand follows
+        #[cfg(feature = "config")]
+        self.load_config(level, app, run)?;
+        run.diagnostics.report(self.apply_env(level, app))?;
+        #[cfg(feature = "config")]
+        run.diagnostics.report(self.apply_config(level, app, run))?;
+        apply_defaults(level, app, streamed);
+        run.diagnostics
+            .report(self.check_possible_values(level, app))?;
+        run.diagnostics.report(check_relations(level, app))?;
+        for flag_def in &level.flags.0 {
+            run.diagnostics
+                .report(flag_def.validate(&app.boolean_flags, &app.counted_flags))?;
+        }
+        for option_def in &level.options.0 {
+            run.diagnostics
+                .report(option_def.validate(&app.single_value_options, &app.multi_value_options))?;
+        }
+        for argument_def in &level.arguments.0 {
+            if streamed != Some(argument_def.name()) {
+                run.diagnostics.report(
+                    argument_def.validate(&app.single_value_arguments, &app.multi_value_arguments),
+                )?;
+            }
+        }
+
+        if let Some((min, max)) = level.occurrences {
+            let name = level.subcommand_names().join(", ");
+            run.diagnostics
+                .report(check_occurrences(&name, min, max, app.subcommands.len()))?;
+            for subcommand in level.subcommands {
+                for (name, min, max) in subcommand.limits() {
+                    let cnt = app.subcommands.iter().filter(|s| s.name == name).count();
+                    run.diagnostics
+                        .report(check_occurrences(name, min, max, cnt))?;
+                }
+            }
+        } else if level.subcommands.iter().any(SubCommandDef::is_required)
+            && app.subcommands.is_empty()
+        {
+            let names = level.subcommand_names();
+            run.diagnostics.report(Err(match names.as_slice() {
+                [name] => format!("subcommand {} is required", name),
+                _ => format!("one of subcommands {} is required", names.join(", ")),
+            }))?;
+        }
+
+        for validator in level.validators {
+            run.diagnostics.report(validator(app))?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(feature = "config")]
+impl AppDef {
+    /// Reads the configuration file once the top level has its own
+    /// command line values, `--config` among them, and before any level
+    /// takes values from the environment. Reports keys nothing defines.
+    fn load_config(&self, level: Level, app: &App, run: &mut Run) -> Result<(), String> {
+        if !self.config_file || !level.root || run.config.is_some() {
+            return Ok(());
+        }
+        let path = match app.single_value_options.get("config") {
+            Some(path) => Some(PathBuf::from(path)),
+            None => config::find(&self.name, |name| self.env_var(name)),
+        };
+        let loaded = match path {
+            Some(path) => config::Config::load(&path),
+            None => Ok(config::Config::default()),
+        };
+        let errors = match &loaded {
+            Ok(config) => self.check_config(config),
+            Err(e) => vec![e.clone()],
+        };
+        run.config = Some(loaded.unwrap_or_default());
+        for error in errors {
+            run.diagnostics.report(Err(error))?;
+        }
+        Ok(())
+    }
 
-        for validator in &self.validators {
-            validator(&app)?;
+    fn check_config(&self, config: &config::Config) -> Vec<String> {
+        let mut errors = vec![];
+        for (name, section) in &config.sections {
+            let path: Vec<&str> = name.split('.').filter(|part| !part.is_empty()).collect();
+            let level = match self.subcommand_level(&path) {
+                Some((level, _)) => level,
+                None => {
+                    errors.push(format!(
+                        "{}:{}: unknown section: {}",
+                        config.path.display(),
+                        section.line,
+                        name
+                    ));
+                    continue;
+                }
+            };
+            for entry in &section.entries {
+                if level.root && entry.key == "config" {
+                    errors.push(format!(
+                        "{}:{}: config cannot be set in the configuration file",
+                        config.path.display(),
+                        entry.line
+                    ));
+                } else if !level.flags.0.iter().any(|fd| fd.name() == entry.key)
+                    && level.options.by_name(&entry.key).is_none()
+                {
+                    errors.push(format!(
+                        "{}:{}: unknown key: {}",
+                        config.path.display(),
+                        entry.line,
+                        entry.key
+                    ));
+                }
+            }
         }
+        errors
+    }
+
+    /// Gives flags and options of `level` that have no value yet the ones
+    /// set in their section of the configuration file.
+    fn apply_config(&self, level: Level, app: &mut App, run: &Run) -> Result<(), String> {
+        let config = match &run.config {
+            Some(config) => config,
+            None => return Ok(()),
+        };
+        let section = match config.sections.get(&run.section.join(".")) {
+            Some(section) => section,
+            None => return Ok(()),
+        };
+        let at = |entry: &config::Entry| format!("{}:{}", config.path.display(), entry.line);
+        let entries = |name: &str| {
+            let name = name.to_string();
+            section
+                .entries
+                .iter()
+                .filter(move |entry| entry.key == name)
+        };
 
+        for flag_def in &level.flags.0 {
+            let name = flag_def.name();
+            if app.boolean_flags.contains_key(&name) || app.counted_flags.contains_key(&name) {
+                continue;
+            }
+            for (idx, entry) in entries(&name).enumerate() {
+                if idx > 0 {
+                    return Err(format!("{}: flag {} may appear only once", at(entry), name));
+                }
+                match flag_def {
+                    FlagDef::BooleanFlagDef(_) => {
+                        let value = parse_bool(OsStr::new(&entry.value)).ok_or_else(|| {
+                            format!(
+                                "{}: flag {} expects a boolean, got: {}",
+                                at(entry),
+                                name,
+                                entry.value
+                            )
+                        })?;
+                        app.boolean_flags.insert(name.clone(), value);
+                    }
+                    FlagDef::CountedFlagDef(_) => {
+                        let cnt = entry.value.parse().map_err(|_| {
+                            format!(
+                                "{}: flag {} expects a count, got: {}",
+                                at(entry),
+                                name,
+                                entry.value
+                            )
+                        })?;
+                        app.counted_flags.insert(name.clone(), cnt);
+                    }
+                }
+                app.sources.insert(name.clone(), ValueSource::Config);
+            }
+        }
+        for option_def in &level.options.0 {
+            let name = option_def.name();
+            if app.single_value_options.contains_key(&name)
+                || app.multi_value_options.contains_key(&name)
+            {
+                continue;
+            }
+            for entry in entries(&name) {
+                app.add_option_value(option_def, OsStr::new(&entry.value))
+                    .map_err(|e| format!("{}: {}", at(entry), e))?;
+                app.sources.insert(name.clone(), ValueSource::Config);
+            }
+        }
         Ok(())
     }
 }
 
+/// Errors found while parsing. Unless recovering, the first one
+/// is returned right away and parsing stops.
+struct Diagnostics {
+    recover: bool,
+    errors: Vec<String>,
+}
+
+impl Diagnostics {
+    fn new(recover: bool) -> Diagnostics {
+        Diagnostics {
+            recover,
+            errors: vec![],
+        }
+    }
+
+    fn report(&mut self, result: Result<(), String>) -> Result<(), String> {
+        match result {
+            Err(error) if self.recover => {
+                self.errors.push(error);
+                Ok(())
+            }
+            result => result,
+        }
+    }
+}
+
+fn arguments_part(arguments: &ArgumentDefs) -> String {
+    let mut s = String::new();
+    for arg in &arguments.0 {
+        match arg {
+            ArgumentDef::SingleValue(real_arg) => {
+                if real_arg.required {
+                    s.push_str(&format!(" {}", real_arg.name))
+                } else {
+                    s.push_str(&format!(" [{}]", real_arg.name))
+                }
+            }
+            ArgumentDef::MultiValue(real_arg) => {
+                match (&real_arg.min_occurences, &real_arg.max_occurences) {
+                    (v1, None) => match v1 {
+                        0 => s.push_str(&format!(" [{}] [...]", real_arg.name)),
+                        _ => {
+                            for _ in 0..*v1 {
+                                s.push_str(&format!(" {}", real_arg.name))
+                            }
+                            s.push_str(" [...]");
+                        }
+                    },
+                    (v1, Some(v2)) => {
+                        for _ in 0..*v1 {
+                            s.push_str(&format!(" {}", real_arg.name))
+                        }
+
+                        if v1 < v2 {
+                            for _ in *v1..*v2 {
+                                s.push_str(&format!(" [{}]", real_arg.name))
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+    s
+}
+
+/// Checks `value` against `possible_values`, it takes the spelling of
+/// the one it matches.
+fn check_choice(
+    matching: &Matching,
+    kind: &str,
+    name: &str,
+    possible_values: &[PossibleValue],
+    value: &mut OsString,
+) -> Result<(), String> {
+    if possible_values.is_empty() {
+        return Ok(());
+    }
+    let given = value.to_string_lossy();
+    match possible_values
+        .iter()
+        .find(|pv| matching.matches(&given, &pv.name))
+    {
+        Some(possible_value) => {
+            *value = OsString::from(&possible_value.name);
+            Ok(())
+        }
+        None => {
+            let names: Vec<&str> = possible_values.iter().map(|pv| pv.name.as_str()).collect();
+            let suggestion = matching.suggest(&given, &names);
+            Err(format!(
+                "{} {} expects one of {}, got: {}{}",
+                kind,
+                name,
+                names.join(", "),
+                given,
+                did_you_mean(suggestion.map(str::to_string))
+            ))
+        }
+    }
+}
+
+/// Checks that no two short names at the same level are the same, that
+/// no two long names, subcommand names or possible values of one option
+/// or argument become the same under `matching`, and that relations between flags, options and arguments
+/// name ones of the same level.
+fn check_names(matching: &Matching, level: Level) -> Result<(), String> {
+    let mut longs = level.flags.longs();
+    longs.extend(level.options.longs());
+    if let Some((a, b)) = matching.find_clash(&longs) {
+        return Err(format!(
+            "long names --{} and --{} cannot be told apart",
+            a, b
+        ));
+    }
+    let mut shorts = level.flags.shorts();
+    shorts.extend(level.options.shorts());
+    let shorts: Vec<String> = shorts.iter().map(char::to_string).collect();
+    let shorts: Vec<&str> = shorts.iter().map(String::as_str).collect();
+    // short names differ in case, `-v` and `-V` are two names
+    if let Some((a, b)) = Matching::default().find_clash(&shorts) {
+        return Err(format!(
+            "short names -{} and -{} cannot be told apart",
+            a, b
+        ));
+    }
+    if let Some((a, b)) = matching.find_clash(&level.subcommand_names()) {
+        return Err(format!(
+            "subcommand names {} and {} cannot be told apart",
+            a, b
+        ));
+    }
+    let choices = level
+        .options
+        .0
+        .iter()
+        .map(|o| (o.name(), o.possible_values(), o.value_matching(*matching)))
+        .chain(level.arguments.0.iter().map(|a| {
+            (
+                a.name().to_string(),
+                a.possible_values(),
+                a.value_matching(*matching),
+            )
+        }));
+    for (name, possible_values, matching) in choices {
+        let names: Vec<&str> = possible_values.iter().map(|pv| pv.name.as_str()).collect();
+        if let Some((a, b)) = matching.find_clash(&names) {
+            return Err(format!(
+                "possible values {} and {} of {} cannot be told apart",
+                a,
+                b,
+                level.describe(&name)
+            ));
+        }
+    }
+    for (name, relations) in level.relations() {
+        if let Some(other) = relations.others().find(|other| !level.defines(other)) {
+            return Err(format!(
+                "{} refers to unknown flag, option or argument {}",
+                level.describe(name),
+                other
+            ));
+        }
+    }
+    for subcommand in level.subcommands {
+        subcommand.check_names(matching)?;
+    }
+    Ok(())
+}
+
 impl AppDefBuilder {
+    fn validate_def(&self) -> Result<(), String> {
+        let subcommand = self.subcommand.as_ref().and_then(|s| s.as_deref());
+        let level = Level {
+            flags: self.flags.as_ref().unwrap_or(&NO_FLAGS),
+            options: self.options.as_ref().unwrap_or(&NO_OPTIONS),
+            arguments: self.arguments.as_ref().unwrap_or(&NO_ARGUMENTS),
+            subcommands: subcommand.map_or(&[], std::slice::from_ref),
+            validators: &[],
+            occurrences: None,
+            trailing: false,
+            order: None,
+            root: true,
+        };
+        check_names(&self.matching.unwrap_or_default(), level)
+    }
+
     pub fn new(name: String) -> AppDefBuilder {
         let mut app_def_builder = AppDefBuilder::default();
         app_def_builder.add_flag(FlagDef::BooleanFlagDef(
             BooleanFlagDefBuilder::new(
-                "verbose".to_string(),
-                Some('V'),
-                Some("verbose".to_string()),
+                VERBOSE.to_string(),
+                Some(VERBOSE_SHORT),
+                Some(VERBOSE.to_string()),
             )
+            .global(true)
             .build()
             .unwrap(),
         ));
@@ -1161,7 +3629,41 @@ impl AppDefBuilder {
         app_def_builder
     }
 
+    /// Drops the `-V, --verbose` flag every app starts with when a flag
+    /// or option of the app takes its name, short or long, so that the
+    /// app can define its own.
+    fn shadow_verbose(&mut self, name: &str, short: Option<char>, long: Option<&str>) {
+        if name != VERBOSE && short != Some(VERBOSE_SHORT) && long != Some(VERBOSE) {
+            return;
+        }
+        if let Some(flag_defs) = &mut self.flags {
+            flag_defs.0.retain(|fd| {
+                !(fd.name() == VERBOSE
+                    && fd.short() == Some(VERBOSE_SHORT)
+                    && fd.long() == Some(VERBOSE)
+                    && fd.is_global())
+            });
+        }
+    }
+
+    /// Reads values missing from the command line and the environment
+    /// from a configuration file, see `config`. The file is looked up in
+    /// the XDG config directories unless the `--config FILE` option this
+    /// adds names another one.
+    #[cfg(feature = "config")]
+    pub fn add_config_file(&mut self) -> &mut Self {
+        self.add_option(
+            OptionDef::new_single_value("config".to_string(), None, Some("config".to_string()))
+                .help(Some("configuration file to read".to_string()))
+                .build()
+                .unwrap()
+                .into(),
+        );
+        self.config_file(true)
+    }
+
     pub fn add_flag(&mut self, flag_def: FlagDef) -> &mut Self {
+        self.shadow_verbose(&flag_def.name(), flag_def.short(), flag_def.long());
         match &mut self.flags {
             Some(ref mut flag_defs) => {
                 flag_defs.add_flag(flag_def);
@@ -1233,6 +3735,7 @@ impl AppDefBuilder {
     }
 
     pub fn add_option(&mut self, option_def: OptionDef) -> &mut Self {
+        self.shadow_verbose(&option_def.name(), option_def.short(), option_def.long());
         match &mut self.options {
             Some(v) => v.0.push(option_def),
             None => self.options = Some(OptionDefs(vec![option_def])),
@@ -1248,11 +3751,11 @@ impl AppDefBuilder {
         if let Some(Some(ref mut hm)) = self.option_groups {
             match hm.entry(group_name.to_string()) {
                 Entry::Occupied(mut g) => {
-                    let mut group = g.get_mut();
+                    let group = g.get_mut();
                     match &mut group.items {
                         None => {
                             let mut hs = IndexSet::new();
-                            let s: String = (&option_def.name()).clone();
+                            let s: String = option_def.name().clone();
                             hs.insert(s);
                             group.items = Some(hs);
                         }
@@ -1266,6 +3769,7 @@ impl AppDefBuilder {
         } else {
             return Err(format!("group does not exist: {}", group_name));
         }
+        self.shadow_verbose(&option_def.name(), option_def.short(), option_def.long());
         match &mut self.options {
             Some(v) => v.0.push(option_def),
             None => self.options = Some(OptionDefs(vec![option_def])),
@@ -1282,15 +3786,103 @@ impl AppDefBuilder {
     }
 }
 
+/// Tokenizes the arguments left once `name` starts streaming. `--` is
+/// dropped as without streaming; flags and options can no longer be
+/// applied, so the first one is an error that ends the stream.
+fn stream_values<'a>(
+    name: &str,
+    mut tokens: parser::TokenStream<'a>,
+    posix: bool,
+    dash_values: parser::DashValues,
+) -> impl Iterator<Item = Result<OsString, String>> + 'a {
+    let name = name.to_string();
+    let mut done = false;
+    std::iter::from_fn(move || loop {
+        if done {
+            return None;
+        }
+        let token = if posix {
+            tokens.next_operand()
+        } else {
+            tokens.next_with(dash_values)
+        }?;
+        let option = match token {
+            parser::Token::Value(value) => return Some(Ok(value)),
+            parser::Token::PositionalSeparator | parser::Token::End => continue,
+            parser::Token::Short(ch) => format!("-{}", ch),
+            parser::Token::Long(long) => format!("--{}", long),
+            parser::Token::PlusShort(ch) => format!("+{}", ch),
+            parser::Token::PlusLong(long) => format!("+{}", long),
+            parser::Token::AttachedValue(value) => value.to_string_lossy().into_owned(),
+            parser::Token::Error(message, position) => {
+                done = true;
+                return Some(Err(format!(
+                    "{} (argument {}, character {})",
+                    message,
+                    position.argument() + 1,
+                    position.char_in_argument() + 1
+                )));
+            }
+        };
+        done = true;
+        return Some(Err(format!(
+            "option {} cannot follow the streamed values of {}",
+            option, name
+        )));
+    })
+}
+
+/// Values of a streamed multi value argument, read as they are consumed.
+/// A flag or option among them is an error, as it can no longer be applied.
+pub struct ValueStream<'a> {
+    name: Option<String>,
+    values: Box<dyn Iterator<Item = Result<OsString, String>> + 'a>,
+}
+
+impl<'a> ValueStream<'a> {
+    fn new<I: Iterator<Item = Result<OsString, String>> + 'a>(
+        name: Option<String>,
+        values: I,
+    ) -> ValueStream<'a> {
+        ValueStream {
+            name,
+            values: Box::new(values),
+        }
+    }
+
+    /// Name of the streamed argument, `None` if parsing never reached it.
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+}
+
+impl<'a> Iterator for ValueStream<'a> {
+    type Item = Result<OsString, String>;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.values.next()
+    }
+}
+
 #[derive(Debug)]
 pub struct App {
     pub name: String,
     pub boolean_flags: IndexMap<String, bool>,
     pub counted_flags: IndexMap<String, u64>,
-    pub single_value_options: IndexMap<String, String>,
-    pub multi_value_options: IndexMap<String, Vec<String>>,
-    pub single_value_arguments: IndexMap<String, String>,
-    pub multi_value_arguments: IndexMap<String, Vec<String>>,
+    pub single_value_options: IndexMap<String, OsString>,
+    pub multi_value_options: IndexMap<String, Vec<OsString>>,
+    pub single_value_arguments: IndexMap<String, OsString>,
+    pub multi_value_arguments: IndexMap<String, Vec<OsString>>,
+    /// names of stdio-enabled options and arguments that were given `-`
+    pub stdio_values: IndexSet<String>,
+    /// abbreviated long names that were given, mapped to the full name
+    pub abbreviations: IndexMap<String, String>,
+    /// parsed subcommands, in the order they were given
+    pub subcommands: Vec<App>,
+    trailing: Vec<OsString>,
+    /// where values that were not given on the command line came from
+    sources: IndexMap<String, ValueSource>,
+    /// flags, options and arguments in the order they were last given
+    given: IndexSet<String>,
 }
 
 impl App {
@@ -1303,6 +3895,291 @@ impl App {
             multi_value_options: IndexMap::new(),
             single_value_arguments: IndexMap::new(),
             multi_value_arguments: IndexMap::new(),
+            stdio_values: IndexSet::new(),
+            abbreviations: IndexMap::new(),
+            subcommands: vec![],
+            trailing: vec![],
+            sources: IndexMap::new(),
+            given: IndexSet::new(),
+        }
+    }
+
+    fn record_given(&mut self, name: &str) {
+        self.given.shift_remove(name);
+        self.given.insert(name.to_string());
+    }
+
+    /// Forgets the values of flag, option or argument `name`.
+    fn remove(&mut self, name: &str) {
+        self.boolean_flags.shift_remove(name);
+        self.counted_flags.shift_remove(name);
+        self.single_value_options.shift_remove(name);
+        self.multi_value_options.shift_remove(name);
+        self.single_value_arguments.shift_remove(name);
+        self.multi_value_arguments.shift_remove(name);
+        self.stdio_values.shift_remove(name);
+        self.sources.shift_remove(name);
+        self.given.shift_remove(name);
+    }
+
+    fn set_flag(&mut self, flag_def: &FlagDef) -> Result<(), String> {
+        self.record_given(&flag_def.name());
+        match flag_def {
+            FlagDef::BooleanFlagDef(bf) => {
+                if self.boolean_flags.contains_key(&bf.name) {
+                    return Err(format!("flag {} may appear only once", bf.name));
+                }
+                self.boolean_flags.insert(bf.name.clone(), true);
+            }
+            FlagDef::CountedFlagDef(cf) => {
+                *self.counted_flags.entry(cf.name.clone()).or_insert(0) += 1;
+            }
+        }
+        Ok(())
+    }
+
+    /// Adds the values of global flags and options that were given
+    /// in subcommands.
+    fn merge_globals(&mut self, globals: App) -> Result<(), String> {
+        for (name, value) in globals.boolean_flags {
+            if self.boolean_flags.contains_key(&name) {
+                return Err(format!("flag {} may appear only once", name));
+            }
+            self.boolean_flags.insert(name, value);
+        }
+        for (name, cnt) in globals.counted_flags {
+            *self.counted_flags.entry(name).or_insert(0) += cnt;
+        }
+        for (name, value) in globals.single_value_options {
+            if self.single_value_options.contains_key(&name) {
+                return Err(format!("option {} may appear only once", name));
+            }
+            self.single_value_options.insert(name, value);
+        }
+        for (name, values) in globals.multi_value_options {
+            self.multi_value_options
+                .entry(name)
+                .or_default()
+                .extend(values);
+        }
+        self.stdio_values.extend(globals.stdio_values);
+        for name in &globals.given {
+            self.record_given(name);
+        }
+        Ok(())
+    }
+
+    fn unset_flag(&mut self, flag_def: &FlagDef) -> Result<(), String> {
+        self.record_given(&flag_def.name());
+        match flag_def {
+            FlagDef::BooleanFlagDef(bf) => {
+                if self.boolean_flags.contains_key(&bf.name) {
+                    return Err(format!("flag {} may appear only once", bf.name));
+                }
+                self.boolean_flags.insert(bf.name.clone(), false);
+                Ok(())
+            }
+            FlagDef::CountedFlagDef(cf) => {
+                Err(format!("counted flag {} cannot be turned off", cf.name))
+            }
+        }
+    }
+
+    fn set_argument_value(&mut self, svad: &SingleValueArgumentDef, value: &OsStr) {
+        self.record_given(&svad.name);
+        if svad.stdio && value == STDIO {
+            self.stdio_values.insert(svad.name.clone());
+        }
+        self.single_value_arguments
+            .insert(svad.name.clone(), value.to_os_string());
+    }
+
+    fn add_option_value(&mut self, option_def: &OptionDef, value: &OsStr) -> Result<(), String> {
+        self.record_given(&option_def.name());
+        match option_def {
+            OptionDef::SingleValue(svod) => {
+                if self.single_value_options.contains_key(&svod.name) {
+                    return Err(format!("option {} may appear only once", svod.name));
+                }
+                if svod.stdio && value == STDIO {
+                    self.stdio_values.insert(svod.name.clone());
+                }
+                self.single_value_options
+                    .insert(svod.name.clone(), value.to_os_string());
+            }
+            OptionDef::MultiValue(mvod) => {
+                self.multi_value_options
+                    .entry(mvod.name.clone())
+                    .or_default()
+                    .push(value.to_os_string());
+            }
+        }
+        Ok(())
+    }
+
+    /// Arguments given after `--`, exactly as they were, when the app
+    /// or subcommand keeps them unparsed.
+    pub fn trailing(&self) -> &[OsString] {
+        &self.trailing
+    }
+
+    /// Name and values of the subcommand that was given, if any.
+    pub fn subcommand(&self) -> Option<(&str, &App)> {
+        self.subcommands
+            .first()
+            .map(|subcommand| (subcommand.name.as_str(), subcommand))
+    }
+
+    /// Whether flag `name` is on, or option or argument `name` has a value.
+    pub fn is_present(&self, name: &str) -> bool {
+        self.boolean_flags.get(name) == Some(&true)
+            || self.counted_flags.get(name).is_some_and(|cnt| *cnt > 0)
+            || self.single_value_options.contains_key(name)
+            || self.multi_value_options.contains_key(name)
+            || self.single_value_arguments.contains_key(name)
+            || self.multi_value_arguments.contains_key(name)
+    }
+
+    /// Where the value of flag, option or argument `name` came from,
+    /// `None` if it has none.
+    pub fn source_of(&self, name: &str) -> Option<ValueSource> {
+        match self.sources.get(name) {
+            Some(source) => Some(*source),
+            None if self.is_present(name) || self.boolean_flags.contains_key(name) => {
+                Some(ValueSource::CommandLine)
+            }
+            None => None,
+        }
+    }
+
+    /// Returns the value of a single value option or argument,
+    /// if it is present and valid UTF-8.
+    pub fn value_of(&self, name: &str) -> Option<&str> {
+        self.value_of_os(name).and_then(OsStr::to_str)
+    }
+
+    pub fn value_of_os(&self, name: &str) -> Option<&OsStr> {
+        self.single_value_options
+            .get(name)
+            .or_else(|| self.single_value_arguments.get(name))
+            .map(OsString::as_os_str)
+    }
+
+    pub fn path_of(&self, name: &str) -> Option<&Path> {
+        self.value_of_os(name).map(Path::new)
+    }
+
+    /// Returns the values of a multi value option or argument,
+    /// if it is present and all of its values are valid UTF-8.
+    pub fn values_of(&self, name: &str) -> Option<Vec<&str>> {
+        self.values_of_os(name)?
+            .iter()
+            .map(|value| value.to_str())
+            .collect()
+    }
+
+    pub fn values_of_os(&self, name: &str) -> Option<&[OsString]> {
+        self.multi_value_options
+            .get(name)
+            .or_else(|| self.multi_value_arguments.get(name))
+            .map(Vec::as_slice)
+    }
+
+    pub fn paths_of(&self, name: &str) -> Option<Vec<&Path>> {
+        self.values_of_os(name)
+            .map(|values| values.iter().map(Path::new).collect())
+    }
+
+    pub fn is_stdio(&self, name: &str) -> bool {
+        self.stdio_values.contains(name)
+    }
+
+    /// Opens the file given as value of `name` for reading,
+    /// or returns stdin if the value stands for it.
+    pub fn reader_of(&self, name: &str) -> Option<io::Result<Box<dyn Read>>> {
+        if self.is_stdio(name) {
+            return Some(Ok(Box::new(io::stdin())));
+        }
+        let path = self.path_of(name)?;
+        Some(File::open(path).map(|file| Box::new(file) as Box<dyn Read>))
+    }
+
+    /// Creates the file given as value of `name` for writing,
+    /// or returns stdout if the value stands for it.
+    pub fn writer_of(&self, name: &str) -> Option<io::Result<Box<dyn Write>>> {
+        if self.is_stdio(name) {
+            return Some(Ok(Box::new(io::stdout())));
         }
+        let path = self.path_of(name)?;
+        Some(File::create(path).map(|file| Box::new(file) as Box<dyn Write>))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        parser, App, AppDefBuilder, ArgumentDef, BasicSubCommandDefBuilder, Diagnostics, Run,
+        SubCommandChainDefBuilder, SubCommandEnumDefBuilder,
+    };
+
+    #[test]
+    fn test_chain_keeps_token_buffer_bounded() {
+        let link = |name: &str| {
+            BasicSubCommandDefBuilder::new(name.to_string())
+                .add_argument(
+                    ArgumentDef::new_single_value("target".to_string())
+                        .build()
+                        .unwrap()
+                        .into(),
+                )
+                .subcommand(Some(Box::new(
+                    BasicSubCommandDefBuilder::new("sub".to_string())
+                        .build()
+                        .unwrap()
+                        .into(),
+                )))
+                .build()
+                .unwrap()
+                .into()
+        };
+        let app_definition = AppDefBuilder::new("tool".to_string())
+            .subcommand(Some(Box::new(
+                SubCommandChainDefBuilder::new(
+                    "run".to_string(),
+                    SubCommandEnumDefBuilder::new()
+                        .add_subcommand(link("a"))
+                        .add_subcommand(link("b"))
+                        .build()
+                        .unwrap()
+                        .into(),
+                )
+                .build()
+                .unwrap()
+                .into(),
+            )))
+            .build()
+            .unwrap();
+
+        // `sub` of the first link ends before `b`, the links after it
+        // must not stay buffered
+        let args = vec!["run", "a", "x", "sub"]
+            .into_iter()
+            .chain((0..1000).flat_map(|_| vec!["b", "x"]));
+        let mut tokens = parser::TokenStream::new(args);
+        let mut run = Run {
+            tokens: &mut tokens,
+            stream: false,
+            diagnostics: &mut Diagnostics::new(false),
+            globals: App::new("tool"),
+            #[cfg(feature = "config")]
+            config: None,
+            #[cfg(feature = "config")]
+            section: vec![],
+        };
+        let (app, _) = app_definition
+            .parse_level(app_definition.level(), App::new("tool"), &[], &mut run)
+            .unwrap();
+        assert_eq!(app.subcommand().unwrap().1.subcommands.len(), 1001);
+        assert!(tokens.buffered() <= 2);
     }
 }
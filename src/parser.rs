@@ -1,8 +1,14 @@
+use std::collections::VecDeque;
+use std::ffi::{OsStr, OsString};
+
 #[derive(Debug, PartialEq)]
-pub enum Token<'a> {
+pub enum Token {
     Short(char),
-    Long(&'a str),
-    Value(&'a str),
+    Long(String),
+    PlusShort(char),
+    PlusLong(String),
+    AttachedValue(OsString),
+    Value(OsString),
     PositionalSeparator,
     Error(String, Position),
     End,
@@ -27,15 +33,51 @@ impl Position {
             char_in_argument,
         }
     }
+
+    pub fn argument(&self) -> usize {
+        self.argument
+    }
+
+    pub fn char_in_argument(&self) -> u32 {
+        self.char_in_argument
+    }
 }
 
+/// Option syntax accepted on top of the usual `-x` and `--name`.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Syntax {
+    /// `-name` is a long option instead of a cluster of short ones, as in
+    /// `find -name` or `java -version`
+    pub single_dash_long: bool,
+    /// `+x` turns flag `x` off, as in `set +x`. Without it arguments
+    /// starting with `+` are plain values, as in `date +%Y`.
+    pub plus_flags: bool,
+    /// `/name` and `/name:value` are long options and `/x` a short one,
+    /// as in Windows tools. Arguments starting with `/` are never values.
+    pub slash_options: bool,
+}
+
+/// Splits arguments into tokens. Arguments are pulled from the source
+/// one at a time, so the whole command line never needs to be in memory.
 pub struct TokenStream<'a> {
-    args: &'a [&'a str],
+    source: Box<dyn Iterator<Item = OsString> + 'a>,
+    /// arguments pulled from the source that may still be needed,
+    /// the first one is argument number `buffer_start`
+    buffer: VecDeque<OsString>,
+    buffer_start: usize,
+    /// arguments that checkpoints not released yet point at
+    pins: Vec<usize>,
+    syntax: Syntax,
     position: Position,
+    /// byte offset of the next unread character of the current argument,
+    /// 0 if the argument has not been started yet
+    offset: usize,
+    /// set after `--`, the remaining arguments are all values
+    positional_only: bool,
     state: State,
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
 enum State {
     Iterating,
     EndToken,
@@ -43,127 +85,430 @@ enum State {
     Done,
 }
 
+/// Saved place in a `TokenStream`, see `TokenStream::checkpoint`.
+#[derive(Debug, PartialEq)]
+pub struct Checkpoint {
+    position: Position,
+    offset: usize,
+    positional_only: bool,
+    state: State,
+}
+
 impl<'a> TokenStream<'a> {
-    pub fn new(args: &'a [&'a str]) -> TokenStream<'a> {
+    pub fn new<I, S>(args: I) -> TokenStream<'a>
+    where
+        I: IntoIterator<Item = S>,
+        I::IntoIter: 'a,
+        S: AsRef<OsStr>,
+    {
+        TokenStream::with_syntax(args, Syntax::default())
+    }
+
+    pub fn with_syntax<I, S>(args: I, syntax: Syntax) -> TokenStream<'a>
+    where
+        I: IntoIterator<Item = S>,
+        I::IntoIter: 'a,
+        S: AsRef<OsStr>,
+    {
+        let mut source = args
+            .into_iter()
+            .map(|arg| arg.as_ref().to_os_string())
+            .fuse();
+        let mut buffer = VecDeque::new();
+        let state = match source.next() {
+            Some(arg) => {
+                buffer.push_back(arg);
+                State::Iterating
+            }
+            None => State::EndToken,
+        };
         TokenStream {
-            args: args,
+            source: Box::new(source),
+            buffer,
+            buffer_start: 0,
+            pins: vec![],
+            syntax,
             position: Position::new(0),
-            state: if args.len() == 0 {
-                State::EndToken
-            } else {
-                State::Iterating
-            },
+            offset: 0,
+            positional_only: false,
+            state,
         }
     }
 
+    pub fn position(&self) -> &Position {
+        &self.position
+    }
+
+    /// Number of arguments pulled from the source and still held.
+    pub fn buffered(&self) -> usize {
+        self.buffer.len()
+    }
+
+    fn current(&self) -> &OsString {
+        &self.buffer[self.position.argument - self.buffer_start]
+    }
+
     pub fn next_argument(&mut self) {
-        if self.position.argument + 1 < self.args.len() {
-            self.position.argument += 1;
-            self.position.char_in_argument = 0;
-        } else {
-            self.state = State::EndToken;
+        self.offset = 0;
+        let next = self.position.argument + 1;
+        if next - self.buffer_start == self.buffer.len() {
+            match self.source.next() {
+                Some(arg) => self.buffer.push_back(arg),
+                None => {
+                    self.state = State::EndToken;
+                    return;
+                }
+            }
         }
+        self.position.argument = next;
+        self.position.char_in_argument = 0;
+        self.trim_buffer();
     }
-}
 
-impl<'a> Iterator for TokenStream<'a> {
-    type Item = Token<'a>;
-    fn next(&mut self) -> Option<Self::Item> {
+    /// Drops buffered arguments that are behind both the current one
+    /// and every unreleased checkpoint.
+    fn trim_buffer(&mut self) {
+        let keep_from = self
+            .pins
+            .iter()
+            .copied()
+            .fold(self.position.argument, usize::min);
+        while self.buffer_start < keep_from {
+            self.buffer.pop_front();
+            self.buffer_start += 1;
+        }
+    }
+
+    /// Saves the current place, including the position inside a cluster of
+    /// short options, so that the stream can go back to it with `restore`.
+    /// Arguments read after a checkpoint are kept until it is released.
+    pub fn checkpoint(&mut self) -> Checkpoint {
+        self.pins.push(self.position.argument);
+        Checkpoint {
+            position: self.position.clone(),
+            offset: self.offset,
+            positional_only: self.positional_only,
+            state: self.state.clone(),
+        }
+    }
+
+    /// Goes back to the place saved in `checkpoint`. The checkpoint stays
+    /// valid and can be restored again until it is released.
+    pub fn restore(&mut self, checkpoint: &Checkpoint) {
+        self.position = checkpoint.position.clone();
+        self.offset = checkpoint.offset;
+        self.positional_only = checkpoint.positional_only;
+        self.state = checkpoint.state.clone();
+    }
+
+    pub fn release(&mut self, checkpoint: Checkpoint) {
+        if let Some(idx) = self
+            .pins
+            .iter()
+            .position(|pin| *pin == checkpoint.position.argument)
+        {
+            self.pins.swap_remove(idx);
+        }
+        self.trim_buffer();
+    }
+
+    /// Continues after a `Token::Error`, skipping the rest of the argument
+    /// that caused it. Does nothing if the stream did not fail.
+    pub fn recover(&mut self) {
+        if self.state == State::Error {
+            self.state = State::Iterating;
+            self.next_argument();
+        }
+    }
+
+    /// Returns the token `next` would return, without consuming it.
+    pub fn peek(&mut self) -> Option<Token> {
+        self.peek_with(DashValues::None)
+    }
+
+    pub fn peek_with(&mut self, dash_values: DashValues) -> Option<Token> {
+        let checkpoint = self.checkpoint();
+        let token = self.next_with(dash_values);
+        self.restore(&checkpoint);
+        self.release(checkpoint);
+        token
+    }
+
+    /// Returns the rest of the current argument as a value for the option
+    /// that was just emitted, if there is one: `red` for `--color=red`,
+    /// `file` for `-ofile` and `-o=file`.
+    pub fn take_attached_value(&mut self) -> Option<OsString> {
+        if self.state != State::Iterating || self.offset == 0 {
+            return None;
+        }
+        let current = self.current();
+        let mut start = self.offset;
+        if is_separator(current.as_encoded_bytes(), start) {
+            start += 1;
+        }
+        let value = tail(current, start).to_os_string();
+        self.next_argument();
+        Some(value)
+    }
+
+    /// Returns the arguments that were not tokenized yet, starting with
+    /// the unread part of the current one.
+    pub fn into_remaining(mut self) -> impl Iterator<Item = OsString> + 'a {
+        let remaining: Vec<OsString> = match self.state {
+            State::Iterating => {
+                let idx = self.position.argument - self.buffer_start;
+                let current = tail(&self.buffer[idx], self.offset).to_os_string();
+                std::iter::once(current)
+                    .chain(self.buffer.drain(idx + 1..))
+                    .collect()
+            }
+            _ => vec![],
+        };
+        remaining.into_iter().chain(self.source)
+    }
+
+    /// Like `next`, but every argument except `--` is returned whole as
+    /// `Token::Value`. The parser uses this once option parsing stopped.
+    pub fn next_operand(&mut self) -> Option<Token> {
+        if self.state == State::Iterating
+            && self.offset == 0
+            && !self.positional_only
+            && self.current() != "--"
+        {
+            let arg = self.current().clone();
+            self.next_argument();
+            return Some(Token::Value(arg));
+        }
+        self.next_with(DashValues::None)
+    }
+
+    /// Like `next`, but an argument starting with a dash is returned as
+    /// `Token::Value` when `dash_values` accepts it. The parser uses this
+    /// when it knows the next argument is going to fill a value.
+    pub fn next_with(&mut self, dash_values: DashValues) -> Option<Token> {
         if self.state == State::Error || self.state == State::Done {
             None
         } else if self.state == State::EndToken {
             self.state = State::Done;
             Some(Token::End)
         } else {
-            let arg = self.args[self.position.argument];
+            let arg = self.current().clone();
+            let bytes = arg.as_encoded_bytes();
 
-            if arg == "--" {
+            if self.offset > 0 {
+                if is_separator(bytes, self.offset) {
+                    let value = tail(&arg, self.offset + 1).to_os_string();
+                    self.next_argument();
+                    return Some(Token::AttachedValue(value));
+                }
+                self.short(&arg)
+            } else if self.positional_only {
+                self.next_argument();
+                Some(Token::Value(arg))
+            } else if bytes == b"--" {
+                self.positional_only = true;
                 self.next_argument();
                 Some(Token::PositionalSeparator)
-            } else if arg.starts_with("--") {
+            } else if bytes.starts_with(b"-") && dash_values.accepts(&arg) {
                 self.next_argument();
-                Some(Token::Long(&arg[2..]))
-            } else if arg.starts_with('-') {
-                let char_len = arg.chars().count();
-                if char_len == 1 {
-                    self.state = State::Error;
-                    return Some(Token::Error(
-                        "invalid value".to_string(),
-                        self.position.clone(),
-                    ));
+                Some(Token::Value(arg))
+            } else if bytes.starts_with(b"--") {
+                self.long(&arg, 2)
+            } else if (bytes.starts_with(b"-")
+                || (self.syntax.plus_flags && bytes.starts_with(b"+")))
+                && bytes.len() > 1
+            {
+                if self.syntax.single_dash_long {
+                    self.long(&arg, 1)
                 } else {
-                    self.position.char_in_argument += 1;
-                    let chr = arg
-                        .chars()
-                        .nth(self.position.char_in_argument as usize)
-                        .unwrap();
-                    if chr == '-' || chr == ' ' {
-                        self.state = State::Error;
-                        return Some(Token::Error(
-                            "invalid character".to_string(),
-                            self.position.clone(),
-                        ));
-                    }
-                    let result = Some(Token::Short(chr));
-                    if self.position.char_in_argument as usize >= char_len - 1 {
-                        self.next_argument();
-                    }
-                    result
+                    self.offset = 1;
+                    self.short(&arg)
                 }
+            } else if self.syntax.slash_options && bytes.starts_with(b"/") && bytes.len() > 1 {
+                self.long(&arg, 1)
             } else {
                 self.next_argument();
                 Some(Token::Value(arg))
             }
         }
     }
+
+    fn short(&mut self, arg: &OsStr) -> Option<Token> {
+        let bytes = arg.as_encoded_bytes();
+        self.position.char_in_argument += 1;
+        let chr = match bytes[self.offset..].utf8_chunks().next() {
+            Some(chunk) => chunk.valid().chars().next(),
+            None => None,
+        };
+        match chr {
+            None | Some('-') | Some(' ') | Some('=') => {
+                self.state = State::Error;
+                Some(Token::Error(
+                    "invalid character".to_string(),
+                    self.position.clone(),
+                ))
+            }
+            Some(chr) => {
+                self.offset += chr.len_utf8();
+                if self.offset >= bytes.len() {
+                    self.next_argument();
+                }
+                if bytes[0] == b'+' {
+                    Some(Token::PlusShort(chr))
+                } else {
+                    Some(Token::Short(chr))
+                }
+            }
+        }
+    }
+
+    fn long(&mut self, arg: &OsStr, prefix_len: usize) -> Option<Token> {
+        let bytes = arg.as_encoded_bytes();
+        let name_end = (prefix_len..bytes.len())
+            .find(|idx| is_separator(bytes, *idx))
+            .unwrap_or(bytes.len());
+        match std::str::from_utf8(&bytes[prefix_len..name_end]) {
+            Ok(name) if !name.is_empty() => {
+                let name_len = name.chars().count();
+                if name_end < bytes.len() {
+                    self.offset = name_end;
+                    self.position.char_in_argument = (prefix_len + name_len - 1) as u32;
+                } else {
+                    self.next_argument();
+                }
+                match bytes[0] {
+                    b'+' => Some(Token::PlusLong(name.to_string())),
+                    b'/' if name_len == 1 => Some(Token::Short(name.chars().next().unwrap())),
+                    _ => Some(Token::Long(name.to_string())),
+                }
+            }
+            _ => {
+                self.state = State::Error;
+                Some(Token::Error(
+                    "invalid character".to_string(),
+                    Position::new_detailed(self.position.argument, prefix_len as u32),
+                ))
+            }
+        }
+    }
+}
+
+/// Checks whether the byte at `idx` separates an option from its value,
+/// `=` in general and also `:` for slash options.
+fn is_separator(arg: &[u8], idx: usize) -> bool {
+    arg[idx] == b'=' || (arg[idx] == b':' && arg[0] == b'/')
+}
+
+/// Which arguments starting with a dash may be taken as values.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DashValues {
+    None,
+    NegativeNumbers,
+    All,
+}
+
+impl DashValues {
+    fn accepts(self, arg: &OsStr) -> bool {
+        match self {
+            DashValues::None => false,
+            DashValues::NegativeNumbers => is_negative_number(arg),
+            DashValues::All => true,
+        }
+    }
+}
+
+fn is_negative_number(arg: &OsStr) -> bool {
+    match arg.to_str() {
+        Some(s) => {
+            s[1..].starts_with(|c: char| c.is_ascii_digit() || c == '.') && s.parse::<f64>().is_ok()
+        }
+        None => false,
+    }
+}
+
+/// Returns the part of `arg` starting at byte `start`.
+pub(crate) fn tail(arg: &OsStr, start: usize) -> &OsStr {
+    // SAFETY: `start` is only ever placed right after an ASCII character
+    // or a complete UTF-8 character, which is a valid split point.
+    unsafe { OsStr::from_encoded_bytes_unchecked(&arg.as_encoded_bytes()[start..]) }
+}
+
+impl<'a> Iterator for TokenStream<'a> {
+    type Item = Token;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_with(DashValues::None)
+    }
 }
 
 #[derive(Debug)]
 pub struct Parser<'a> {
-    args: &'a [&'a str],
+    args: &'a [&'a OsStr],
+    syntax: Syntax,
 }
 
 impl<'a> Parser<'a> {
-    pub fn new(args: &'a [&'a str]) -> Parser {
-        Parser { args: args }
+    pub fn new(args: &'a [&'a OsStr]) -> Parser<'a> {
+        Parser::with_syntax(args, Syntax::default())
     }
 
-    pub fn iter(&self) -> TokenStream {
-        TokenStream::new(self.args)
+    pub fn with_syntax(args: &'a [&'a OsStr], syntax: Syntax) -> Parser<'a> {
+        Parser { args, syntax }
+    }
+
+    pub fn iter(&self) -> TokenStream<'a> {
+        TokenStream::with_syntax(self.args.iter(), self.syntax)
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{Parser, Position, Token};
+    use super::{DashValues, Parser, Position, Syntax, Token, TokenStream};
+    use std::ffi::OsStr;
+
+    fn os_args<'a>(args: &[&'a str]) -> Vec<&'a OsStr> {
+        args.iter().map(|arg| OsStr::new(*arg)).collect()
+    }
+
     #[test]
     fn test_parser() {
-        let parser = Parser::new(&[]);
+        let args = os_args(&[]);
+        let parser = Parser::new(&args);
         let tokens: Vec<Token> = parser.iter().collect();
         assert_eq!(tokens, vec![Token::End]);
 
-        let parser = Parser::new(&["-v"]);
+        let args = os_args(&["-v"]);
+        let parser = Parser::new(&args);
         let t: Vec<Token> = parser.iter().collect();
         assert_eq!(t, vec![Token::Short('v'), Token::End]);
 
-        let parser = Parser::new(&["-s", "--long"]);
+        let args = os_args(&["-s", "--long"]);
+        let parser = Parser::new(&args);
         let t: Vec<Token> = parser.iter().collect();
-        assert_eq!(t, vec![Token::Short('s'), Token::Long("long"), Token::End]);
+        assert_eq!(
+            t,
+            vec![
+                Token::Short('s'),
+                Token::Long("long".to_string()),
+                Token::End
+            ]
+        );
 
-        let parser = Parser::new(&["-s", "--long", "value"]);
+        let args = os_args(&["-s", "--long", "value"]);
+        let parser = Parser::new(&args);
         let t: Vec<Token> = parser.iter().collect();
         assert_eq!(
             t,
             vec![
                 Token::Short('s'),
-                Token::Long("long"),
-                Token::Value("value"),
+                Token::Long("long".to_string()),
+                Token::Value("value".into()),
                 Token::End
             ]
         );
 
         //test error handling
-        let parser = Parser::new(&["-v "]);
+        let args = os_args(&["-v "]);
+        let parser = Parser::new(&args);
         let t: Vec<Token> = parser.iter().collect();
         assert_eq!(
             t,
@@ -176,7 +521,8 @@ mod tests {
             ]
         );
 
-        let parser = Parser::new(&["- "]);
+        let args = os_args(&["- "]);
+        let parser = Parser::new(&args);
         let t: Vec<Token> = parser.iter().collect();
         assert_eq!(
             t,
@@ -186,4 +532,474 @@ mod tests {
             )]
         );
     }
+
+    #[test]
+    fn test_attached_values() {
+        let args = os_args(&["--color=red", "--color=", "-ofile", "-o=file"]);
+        let parser = Parser::new(&args);
+        let t: Vec<Token> = parser.iter().collect();
+        assert_eq!(
+            t,
+            vec![
+                Token::Long("color".to_string()),
+                Token::AttachedValue("red".into()),
+                Token::Long("color".to_string()),
+                Token::AttachedValue("".into()),
+                Token::Short('o'),
+                Token::Short('f'),
+                Token::Short('i'),
+                Token::Short('l'),
+                Token::Short('e'),
+                Token::Short('o'),
+                Token::AttachedValue("file".into()),
+                Token::End
+            ]
+        );
+
+        let args = os_args(&["-vofile", "--color=red", "value"]);
+        let parser = Parser::new(&args);
+        let mut tokens = parser.iter();
+        assert_eq!(tokens.next(), Some(Token::Short('v')));
+        assert_eq!(tokens.next(), Some(Token::Short('o')));
+        assert_eq!(tokens.take_attached_value(), Some("file".into()));
+        assert_eq!(tokens.next(), Some(Token::Long("color".to_string())));
+        assert_eq!(tokens.take_attached_value(), Some("red".into()));
+        assert_eq!(tokens.take_attached_value(), None);
+        assert_eq!(tokens.next(), Some(Token::Value("value".into())));
+        assert_eq!(tokens.take_attached_value(), None);
+        assert_eq!(tokens.next(), Some(Token::End));
+
+        let args = os_args(&["--=red"]);
+        let parser = Parser::new(&args);
+        let t: Vec<Token> = parser.iter().collect();
+        assert_eq!(
+            t,
+            vec![Token::Error(
+                "invalid character".to_string(),
+                Position::new_detailed(0, 2)
+            )]
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_non_utf8_arguments() {
+        use std::os::unix::ffi::OsStrExt;
+
+        let file = OsStr::from_bytes(b"caf\xe9.txt");
+        let attached = OsStr::from_bytes(b"--output=caf\xe9.txt");
+        let cluster = OsStr::from_bytes(b"-ocaf\xe9.txt");
+        let args = vec![file, attached, cluster];
+        let parser = Parser::new(&args);
+        let mut tokens = parser.iter();
+        assert_eq!(tokens.next(), Some(Token::Value(file.to_os_string())));
+        assert_eq!(tokens.next(), Some(Token::Long("output".to_string())));
+        assert_eq!(
+            tokens.next(),
+            Some(Token::AttachedValue(file.to_os_string()))
+        );
+        assert_eq!(tokens.next(), Some(Token::Short('o')));
+        assert_eq!(tokens.take_attached_value(), Some(file.to_os_string()));
+        assert_eq!(tokens.next(), Some(Token::End));
+
+        let args = vec![OsStr::from_bytes(b"--caf\xe9")];
+        let parser = Parser::new(&args);
+        let t: Vec<Token> = parser.iter().collect();
+        assert_eq!(
+            t,
+            vec![Token::Error(
+                "invalid character".to_string(),
+                Position::new_detailed(0, 2)
+            )]
+        );
+
+        let args = vec![OsStr::from_bytes(b"-v\xe9")];
+        let parser = Parser::new(&args);
+        let t: Vec<Token> = parser.iter().collect();
+        assert_eq!(
+            t,
+            vec![
+                Token::Short('v'),
+                Token::Error(
+                    "invalid character".to_string(),
+                    Position::new_detailed(0, 2)
+                )
+            ]
+        );
+    }
+
+    #[test]
+    fn test_stdio_value() {
+        let args = os_args(&["-", "-v", "-"]);
+        let parser = Parser::new(&args);
+        let t: Vec<Token> = parser.iter().collect();
+        assert_eq!(
+            t,
+            vec![
+                Token::Value("-".into()),
+                Token::Short('v'),
+                Token::Value("-".into()),
+                Token::End
+            ]
+        );
+    }
+
+    #[test]
+    fn test_dash_values() {
+        let args = os_args(&["-5", "-3.2", "-1e3", "-x", "--", "--long", "-.5"]);
+        let parser = Parser::new(&args);
+        let mut tokens = parser.iter();
+        let mut t = vec![];
+        while let Some(token) = tokens.next_with(DashValues::NegativeNumbers) {
+            t.push(token);
+        }
+        assert_eq!(
+            t,
+            vec![
+                Token::Value("-5".into()),
+                Token::Value("-3.2".into()),
+                Token::Value("-1e3".into()),
+                Token::Short('x'),
+                Token::PositionalSeparator,
+                Token::Value("--long".into()),
+                Token::Value("-.5".into()),
+                Token::End
+            ]
+        );
+
+        let parser = Parser::new(&args);
+        let mut tokens = parser.iter();
+        let mut t = vec![];
+        while let Some(token) = tokens.next_with(DashValues::All) {
+            t.push(token);
+        }
+        assert_eq!(
+            t,
+            vec![
+                Token::Value("-5".into()),
+                Token::Value("-3.2".into()),
+                Token::Value("-1e3".into()),
+                Token::Value("-x".into()),
+                Token::PositionalSeparator,
+                Token::Value("--long".into()),
+                Token::Value("-.5".into()),
+                Token::End
+            ]
+        );
+
+        let args = os_args(&["-5", "-inf"]);
+        let parser = Parser::new(&args);
+        let t: Vec<Token> = parser.iter().collect();
+        assert_eq!(
+            t,
+            vec![
+                Token::Short('5'),
+                Token::Short('i'),
+                Token::Short('n'),
+                Token::Short('f'),
+                Token::End
+            ]
+        );
+    }
+
+    #[test]
+    fn test_plus_values() {
+        let args = os_args(&["+%Y-%m-%d", "-u"]);
+        let parser = Parser::new(&args);
+        let t: Vec<Token> = parser.iter().collect();
+        assert_eq!(
+            t,
+            vec![
+                Token::Value("+%Y-%m-%d".into()),
+                Token::Short('u'),
+                Token::End
+            ]
+        );
+    }
+
+    #[test]
+    fn test_plus_flags() {
+        let syntax = Syntax {
+            plus_flags: true,
+            ..Syntax::default()
+        };
+        let args = os_args(&["+x", "-e", "+ov", "+", "-"]);
+        let parser = Parser::with_syntax(&args, syntax);
+        let t: Vec<Token> = parser.iter().collect();
+        assert_eq!(
+            t,
+            vec![
+                Token::PlusShort('x'),
+                Token::Short('e'),
+                Token::PlusShort('o'),
+                Token::PlusShort('v'),
+                Token::Value("+".into()),
+                Token::Value("-".into()),
+                Token::End
+            ]
+        );
+
+        let args = os_args(&["+o", "vi"]);
+        let parser = Parser::with_syntax(&args, syntax);
+        let mut tokens = parser.iter();
+        assert_eq!(tokens.next(), Some(Token::PlusShort('o')));
+        assert_eq!(tokens.take_attached_value(), None);
+        assert_eq!(tokens.next(), Some(Token::Value("vi".into())));
+    }
+
+    #[test]
+    fn test_single_dash_long() {
+        let syntax = Syntax {
+            single_dash_long: true,
+            ..Syntax::default()
+        };
+        let args = os_args(&["-name", "*.rs", "-type=f", "--print", "-x"]);
+        let parser = Parser::with_syntax(&args, syntax);
+        let t: Vec<Token> = parser.iter().collect();
+        assert_eq!(
+            t,
+            vec![
+                Token::Long("name".to_string()),
+                Token::Value("*.rs".into()),
+                Token::Long("type".to_string()),
+                Token::AttachedValue("f".into()),
+                Token::Long("print".to_string()),
+                Token::Long("x".to_string()),
+                Token::End
+            ]
+        );
+
+        let syntax = Syntax {
+            single_dash_long: true,
+            plus_flags: true,
+            ..Syntax::default()
+        };
+        let args = os_args(&["+debug", "-=x"]);
+        let parser = Parser::with_syntax(&args, syntax);
+        let t: Vec<Token> = parser.iter().collect();
+        assert_eq!(
+            t,
+            vec![
+                Token::PlusLong("debug".to_string()),
+                Token::Error(
+                    "invalid character".to_string(),
+                    Position::new_detailed(1, 1)
+                )
+            ]
+        );
+    }
+
+    #[test]
+    fn test_slash_options() {
+        let syntax = Syntax {
+            slash_options: true,
+            ..Syntax::default()
+        };
+        let args = os_args(&["/s", "/out:file.txt", "/level=3", "/", "-v", "--long"]);
+        let parser = Parser::with_syntax(&args, syntax);
+        let t: Vec<Token> = parser.iter().collect();
+        assert_eq!(
+            t,
+            vec![
+                Token::Short('s'),
+                Token::Long("out".to_string()),
+                Token::AttachedValue("file.txt".into()),
+                Token::Long("level".to_string()),
+                Token::AttachedValue("3".into()),
+                Token::Value("/".into()),
+                Token::Short('v'),
+                Token::Long("long".to_string()),
+                Token::End
+            ]
+        );
+
+        let args = os_args(&["/out:file.txt", "-o:x"]);
+        let parser = Parser::with_syntax(&args, syntax);
+        let mut tokens = parser.iter();
+        assert_eq!(tokens.next(), Some(Token::Long("out".to_string())));
+        assert_eq!(tokens.take_attached_value(), Some("file.txt".into()));
+        assert_eq!(tokens.next(), Some(Token::Short('o')));
+        assert_eq!(tokens.take_attached_value(), Some(":x".into()));
+    }
+
+    #[test]
+    fn test_token_stream_from_iterator() {
+        let args = (0..3).map(|i| format!("-{}", i));
+        let t: Vec<Token> = TokenStream::new(args).collect();
+        assert_eq!(
+            t,
+            vec![
+                Token::Short('0'),
+                Token::Short('1'),
+                Token::Short('2'),
+                Token::End
+            ]
+        );
+
+        let mut tokens = TokenStream::new(vec!["-ab", "c", "d"]);
+        assert_eq!(tokens.next(), Some(Token::Short('a')));
+        let remaining: Vec<_> = tokens.into_remaining().collect();
+        assert_eq!(remaining, vec!["b", "c", "d"]);
+
+        let mut tokens = TokenStream::new(vec!["-a"]);
+        assert_eq!(tokens.next(), Some(Token::Short('a')));
+        assert_eq!(tokens.into_remaining().count(), 0);
+    }
+
+    #[test]
+    fn test_checkpoints() {
+        let mut tokens = TokenStream::new(vec!["-abc", "--long=x", "value"]);
+        assert_eq!(tokens.peek(), Some(Token::Short('a')));
+        assert_eq!(tokens.next(), Some(Token::Short('a')));
+
+        let inside_cluster = tokens.checkpoint();
+        assert_eq!(tokens.next(), Some(Token::Short('b')));
+        assert_eq!(tokens.next(), Some(Token::Short('c')));
+        assert_eq!(tokens.next(), Some(Token::Long("long".to_string())));
+        assert_eq!(tokens.position(), &Position::new_detailed(1, 5));
+        tokens.restore(&inside_cluster);
+        assert_eq!(tokens.position(), &Position::new_detailed(0, 1));
+        assert_eq!(tokens.peek(), Some(Token::Short('b')));
+        assert_eq!(tokens.next(), Some(Token::Short('b')));
+        assert_eq!(tokens.position(), &Position::new_detailed(0, 2));
+
+        tokens.restore(&inside_cluster);
+        let t: Vec<Token> = tokens.by_ref().collect();
+        assert_eq!(
+            t,
+            vec![
+                Token::Short('b'),
+                Token::Short('c'),
+                Token::Long("long".to_string()),
+                Token::AttachedValue("x".into()),
+                Token::Value("value".into()),
+                Token::End
+            ]
+        );
+        assert_eq!(tokens.next(), None);
+        assert_eq!(tokens.peek(), None);
+
+        tokens.restore(&inside_cluster);
+        tokens.release(inside_cluster);
+        assert_eq!(tokens.next(), Some(Token::Short('b')));
+        tokens.next_argument();
+        assert_eq!(tokens.buffer.len(), 2);
+    }
+
+    #[test]
+    fn test_checkpoint_replays_streamed_arguments() {
+        let args = (0..4).map(|i| format!("-{}", i));
+        let mut tokens = TokenStream::new(args);
+        let start = tokens.checkpoint();
+        assert_eq!(tokens.by_ref().count(), 5);
+        tokens.restore(&start);
+        tokens.release(start);
+        assert_eq!(tokens.next(), Some(Token::Short('0')));
+        assert_eq!(tokens.next(), Some(Token::Short('1')));
+        assert_eq!(tokens.buffer.len(), 2);
+
+        let before_error = tokens.checkpoint();
+        let mut broken = TokenStream::new(vec!["-a-"]);
+        assert_eq!(broken.next(), Some(Token::Short('a')));
+        let checkpoint = broken.checkpoint();
+        assert_eq!(
+            broken.next(),
+            Some(Token::Error(
+                "invalid character".to_string(),
+                Position::new_detailed(0, 2)
+            ))
+        );
+        assert_eq!(broken.next(), None);
+        broken.restore(&checkpoint);
+        assert_eq!(
+            broken.next(),
+            Some(Token::Error(
+                "invalid character".to_string(),
+                Position::new_detailed(0, 2)
+            ))
+        );
+
+        tokens.restore(&before_error);
+        let remaining: Vec<_> = tokens.into_remaining().collect();
+        assert_eq!(remaining, vec!["-2", "-3"]);
+    }
+
+    #[test]
+    fn test_recover() {
+        let mut tokens = TokenStream::new(vec!["-a-b", "--=x", "-c", "value"]);
+        let mut t = vec![];
+        while let Some(token) = tokens.next() {
+            if let Token::Error(..) = token {
+                tokens.recover();
+            }
+            t.push(token);
+        }
+        assert_eq!(
+            t,
+            vec![
+                Token::Short('a'),
+                Token::Error(
+                    "invalid character".to_string(),
+                    Position::new_detailed(0, 2)
+                ),
+                Token::Error(
+                    "invalid character".to_string(),
+                    Position::new_detailed(1, 2)
+                ),
+                Token::Short('c'),
+                Token::Value("value".into()),
+                Token::End
+            ]
+        );
+
+        let mut tokens = TokenStream::new(vec!["--="]);
+        assert_eq!(
+            tokens.next(),
+            Some(Token::Error(
+                "invalid character".to_string(),
+                Position::new_detailed(0, 2)
+            ))
+        );
+        tokens.recover();
+        assert_eq!(tokens.next(), Some(Token::End));
+        assert_eq!(tokens.next(), None);
+    }
+
+    #[test]
+    fn test_operands() {
+        let mut tokens = TokenStream::new(vec!["-ab", "-c", "--long", "--", "-d"]);
+        assert_eq!(tokens.next(), Some(Token::Short('a')));
+        assert_eq!(tokens.next_operand(), Some(Token::Short('b')));
+        assert_eq!(tokens.next_operand(), Some(Token::Value("-c".into())));
+        assert_eq!(tokens.next_operand(), Some(Token::Value("--long".into())));
+        assert_eq!(tokens.next_operand(), Some(Token::PositionalSeparator));
+        assert_eq!(tokens.next_operand(), Some(Token::Value("-d".into())));
+        assert_eq!(tokens.next_operand(), Some(Token::End));
+        assert_eq!(tokens.next_operand(), None);
+    }
+
+    #[test]
+    fn test_positional_separator() {
+        let mut tokens = TokenStream::new(vec!["-a", "--", "-b", "--", "@x"]);
+        assert_eq!(tokens.next(), Some(Token::Short('a')));
+        let before = tokens.checkpoint();
+        assert_eq!(tokens.next(), Some(Token::PositionalSeparator));
+        let after = tokens.checkpoint();
+        assert_eq!(tokens.next(), Some(Token::Value("-b".into())));
+        assert_eq!(tokens.next(), Some(Token::Value("--".into())));
+
+        tokens.restore(&before);
+        assert_eq!(tokens.next(), Some(Token::PositionalSeparator));
+        tokens.restore(&after);
+        let t: Vec<Token> = tokens.collect();
+        assert_eq!(
+            t,
+            vec![
+                Token::Value("-b".into()),
+                Token::Value("--".into()),
+                Token::Value("@x".into()),
+                Token::End
+            ]
+        );
+    }
 }
@@ -0,0 +1,113 @@
+//! Expansion of `@file` arguments into the arguments listed in the file.
+//!
+//! Files are split into arguments with shell quoting rules, may refer to
+//! other response files relative to their own location, and `@@text` stands
+//! for a literal `@text` argument. Arguments after `--` are kept as they are.
+
+use std::ffi::{OsStr, OsString};
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::parser::{tail, Position};
+use crate::shell_words;
+
+#[derive(Debug, PartialEq)]
+pub struct Error {
+    pub message: String,
+    /// command line argument that referred to the failing response file
+    pub position: Position,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} (argument {})",
+            self.message,
+            self.position.argument() + 1
+        )
+    }
+}
+
+enum Reference<'a> {
+    File(&'a OsStr),
+    Literal(&'a OsStr),
+}
+
+fn reference(arg: &OsStr) -> Option<Reference<'_>> {
+    let bytes = arg.as_encoded_bytes();
+    if bytes.starts_with(b"@@") {
+        Some(Reference::Literal(tail(arg, 1)))
+    } else if bytes.starts_with(b"@") && bytes.len() > 1 {
+        Some(Reference::File(tail(arg, 1)))
+    } else {
+        None
+    }
+}
+
+/// Adds `arg` to `expanded` as it is, noting whether it is `--`.
+fn keep(arg: &OsStr, expanded: &mut Vec<OsString>, separated: &mut bool) {
+    *separated = *separated || arg == "--";
+    expanded.push(arg.to_os_string());
+}
+
+pub fn expand<S: AsRef<OsStr>>(args: &[S]) -> Result<Vec<OsString>, Error> {
+    let mut expanded = vec![];
+    let mut separated = false;
+    for (idx, arg) in args.iter().enumerate() {
+        let arg = arg.as_ref();
+        match reference(arg).filter(|_| !separated) {
+            Some(Reference::File(path)) => {
+                expand_file(Path::new(path), &mut vec![], &mut expanded, &mut separated).map_err(
+                    |message| Error {
+                        message,
+                        position: Position::new(idx),
+                    },
+                )?
+            }
+            Some(Reference::Literal(value)) => expanded.push(value.to_os_string()),
+            None => keep(arg, &mut expanded, &mut separated),
+        }
+    }
+    Ok(expanded)
+}
+
+fn expand_file(
+    path: &Path,
+    included_from: &mut Vec<PathBuf>,
+    expanded: &mut Vec<OsString>,
+    separated: &mut bool,
+) -> Result<(), String> {
+    let path = fs::canonicalize(path)
+        .map_err(|e| format!("cannot read response file {}: {}", path.display(), e))?;
+    if included_from.contains(&path) {
+        return Err(format!("response file {} includes itself", path.display()));
+    }
+    let content = fs::read_to_string(&path)
+        .map_err(|e| format!("cannot read response file {}: {}", path.display(), e))?;
+    let words = shell_words::split(&content).map_err(|e| {
+        let line = content
+            .chars()
+            .take(e.offset)
+            .filter(|c| *c == '\n')
+            .count()
+            + 1;
+        format!("{}:{}: {}", path.display(), line, e.message)
+    })?;
+
+    let dir = path.parent().map(Path::to_path_buf).unwrap_or_default();
+    included_from.push(path);
+    for word in words {
+        let word = OsString::from(word);
+        match reference(&word).filter(|_| !*separated) {
+            Some(Reference::File(nested)) => {
+                expand_file(&dir.join(nested), included_from, expanded, separated)?
+            }
+            Some(Reference::Literal(value)) => expanded.push(value.to_os_string()),
+            None => keep(&word, expanded, separated),
+        }
+    }
+    included_from.pop();
+    Ok(())
+}
@@ -0,0 +1,168 @@
+//! Splitting of text into words following POSIX shell quoting rules.
+
+#[derive(Debug, PartialEq)]
+pub struct SplitError {
+    pub message: String,
+    /// index of the character that caused the error
+    pub offset: usize,
+}
+
+#[derive(PartialEq)]
+enum Quote {
+    None,
+    Single,
+    Double,
+}
+
+/// Splits `text` into words separated by whitespace. Single quotes keep
+/// everything literally, double quotes allow `\` to escape `$`, `` ` ``,
+/// `"`, `\` and newline, an unquoted `\` escapes any character and a `#`
+/// at the start of a word starts a comment running to the end of the line.
+pub fn split(text: &str) -> Result<Vec<String>, SplitError> {
+    let mut words = vec![];
+    let mut word = String::new();
+    let mut in_word = false;
+    let mut quote = Quote::None;
+    let mut quote_start = 0;
+    let mut chars = text.chars().enumerate().peekable();
+
+    while let Some((idx, chr)) = chars.next() {
+        match quote {
+            Quote::Single => {
+                if chr == '\'' {
+                    quote = Quote::None;
+                } else {
+                    word.push(chr);
+                }
+            }
+            Quote::Double => match chr {
+                '"' => quote = Quote::None,
+                '\\' => match chars.peek() {
+                    Some((_, '\n')) => {
+                        chars.next();
+                    }
+                    Some((_, next @ '$'))
+                    | Some((_, next @ '`'))
+                    | Some((_, next @ '"'))
+                    | Some((_, next @ '\\')) => {
+                        word.push(*next);
+                        chars.next();
+                    }
+                    _ => word.push(chr),
+                },
+                _ => word.push(chr),
+            },
+            Quote::None => match chr {
+                '\'' | '"' => {
+                    quote = if chr == '\'' {
+                        Quote::Single
+                    } else {
+                        Quote::Double
+                    };
+                    quote_start = idx;
+                    in_word = true;
+                }
+                '\\' => match chars.next() {
+                    Some((_, '\n')) => (),
+                    Some((_, next)) => {
+                        word.push(next);
+                        in_word = true;
+                    }
+                    None => {
+                        return Err(SplitError {
+                            message: "unterminated escape".to_string(),
+                            offset: idx,
+                        })
+                    }
+                },
+                '#' if !in_word => {
+                    while let Some((_, next)) = chars.peek() {
+                        if *next == '\n' {
+                            break;
+                        }
+                        chars.next();
+                    }
+                }
+                ' ' | '\t' | '\n' | '\r' => {
+                    if in_word {
+                        words.push(std::mem::take(&mut word));
+                        in_word = false;
+                    }
+                }
+                _ => {
+                    word.push(chr);
+                    in_word = true;
+                }
+            },
+        }
+    }
+
+    match quote {
+        Quote::None => {
+            if in_word {
+                words.push(word);
+            }
+            Ok(words)
+        }
+        Quote::Single => Err(SplitError {
+            message: "unterminated single quote".to_string(),
+            offset: quote_start,
+        }),
+        Quote::Double => Err(SplitError {
+            message: "unterminated double quote".to_string(),
+            offset: quote_start,
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{split, SplitError};
+
+    #[test]
+    fn test_split() {
+        assert_eq!(split(""), Ok(vec![]));
+        assert_eq!(
+            split("  a\tb\nc  "),
+            Ok(vec!["a".to_string(), "b".to_string(), "c".to_string()])
+        );
+        assert_eq!(
+            split(r#"--opt 'a b' "c\"d" e\ f '' "$x\y""#),
+            Ok(vec![
+                "--opt".to_string(),
+                "a b".to_string(),
+                "c\"d".to_string(),
+                "e f".to_string(),
+                "".to_string(),
+                "$x\\y".to_string()
+            ])
+        );
+        assert_eq!(
+            split("a#b # comment 'x\nc \\\nd"),
+            Ok(vec!["a#b".to_string(), "c".to_string(), "d".to_string()])
+        );
+        assert_eq!(split("'it''s'"), Ok(vec!["its".to_string()]));
+
+        assert_eq!(
+            split("a 'b"),
+            Err(SplitError {
+                message: "unterminated single quote".to_string(),
+                offset: 2
+            })
+        );
+        assert_eq!(
+            split("ab \"c"),
+            Err(SplitError {
+                message: "unterminated double quote".to_string(),
+                offset: 3
+            })
+        );
+        assert_eq!(
+            split("a\\"),
+            Err(SplitError {
+                message: "unterminated escape".to_string(),
+                offset: 1
+            })
+        );
+    }
+}
@@ -0,0 +1,120 @@
+use rust_yap::{
+    AppDefBuilder, ArgumentDef, BasicSubCommandDefBuilder, BooleanFlagDefBuilder, OptionDef, SubCommandChainDefBuilder,
+    SubCommandEnumDefBuilder,
+};
+
+
+#[test]
+fn test_app_with_abbreviations() {
+    let app_definition = AppDefBuilder::new("fetch".to_string())
+        .abbreviations(true)
+        .add_flag(
+                BooleanFlagDefBuilder::new("version".to_string(), None, Some("version".to_string()))
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .add_flag(
+                BooleanFlagDefBuilder::new("insecure".to_string(), None, Some("insecure".to_string()))
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .add_option(
+                OptionDef::new_single_value("output".to_string(), None, Some("output".to_string()))
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .add_option(
+                OptionDef::new_single_value("out".to_string(), None, Some("out".to_string()))
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .build()
+        .unwrap();
+
+    let app = app_definition.parse_args(&["--verb", "--ins", "--outp=file"]).unwrap();
+    assert_eq!(app.boolean_flags.get("verbose"), Some(&true));
+    assert_eq!(app.boolean_flags.get("insecure"), Some(&true));
+    assert_eq!(app.value_of("output"), Some("file"));
+    assert_eq!(app.abbreviations.get("verb"), Some(&"verbose".to_string()));
+    assert_eq!(app.abbreviations.get("ins"), Some(&"insecure".to_string()));
+    assert_eq!(app.abbreviations.get("outp"), Some(&"output".to_string()));
+
+    let app = app_definition.parse_args(&["--out", "file"]).unwrap();
+    assert_eq!(app.value_of("out"), Some("file"));
+    assert_eq!(app.value_of("output"), None);
+    assert!(app.abbreviations.is_empty());
+
+    let app = app_definition.parse_args(&["--ver"]);
+    assert_eq!(app.unwrap_err(), "option --ver is ambiguous, it could be --verbose, --version");
+    let app = app_definition.parse_args(&["--x"]);
+    assert_eq!(app.unwrap_err(), "unknown option: --x");
+}
+
+#[test]
+fn test_app_without_abbreviations() {
+    let app_definition = AppDefBuilder::new("fetch".to_string())
+        .build()
+        .unwrap();
+
+    let app = app_definition.parse_args(&["--verb"]);
+    assert_eq!(app.unwrap_err(), "unknown option: --verb");
+}
+
+#[test]
+fn test_app_with_abbreviated_chain_links() {
+    let app_definition = AppDefBuilder::new("deploy".to_string())
+        .abbreviations(true)
+        .subcommand(Some(Box::new(
+                SubCommandChainDefBuilder::new(
+                    "run".to_string(),
+                    SubCommandEnumDefBuilder::new()
+                        .add_subcommand(
+                                BasicSubCommandDefBuilder::new("web".to_string())
+                                    .add_argument(
+                                            ArgumentDef::new_single_value("target".to_string())
+                                                .build()
+                                                .unwrap()
+                                                .into()
+                                    )
+                                    .build()
+                                    .unwrap()
+                                    .into()
+                        )
+                        .add_subcommand(
+                                BasicSubCommandDefBuilder::new("worker".to_string())
+                                    .add_argument(
+                                            ArgumentDef::new_single_value("target".to_string())
+                                                .build()
+                                                .unwrap()
+                                                .into()
+                                    )
+                                    .build()
+                                    .unwrap()
+                                    .into()
+                        )
+                        .build()
+                        .unwrap()
+                        .into()
+                )
+                    .build()
+                    .unwrap()
+                    .into()
+        )))
+        .build()
+        .unwrap();
+
+    let app = app_definition.parse_args(&["run", "wor", "a", "we", "b"]).unwrap();
+    let (_, run) = app.subcommand().unwrap();
+    let links: Vec<&str> = run.subcommands.iter().map(|link| link.name.as_str()).collect();
+    assert_eq!(links, vec!["worker", "web"]);
+    assert_eq!(run.subcommands[0].value_of("target"), Some("a"));
+    assert_eq!(run.subcommands[1].value_of("target"), Some("b"));
+    assert_eq!(run.abbreviations.get("we"), Some(&"web".to_string()));
+
+    let app = app_definition.parse_args(&["run", "web", "a", "w"]);
+    assert_eq!(app.unwrap_err(), "subcommand w is ambiguous, it could be web, worker");
+}
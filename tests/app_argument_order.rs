@@ -0,0 +1,146 @@
+use rust_yap::{
+    AppDefBuilder, ArgumentDef, ArgumentOrder, BasicSubCommandDefBuilder, BooleanFlagDefBuilder,
+    OptionDef,
+};
+
+
+#[test]
+fn test_app_with_permuted_arguments() {
+    let app_definition = AppDefBuilder::new("nice".to_string())
+        .add_flag(
+                BooleanFlagDefBuilder::new("force".to_string(), Some('f'), Some("force".to_string()))
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .add_option(
+                OptionDef::new_single_value("level".to_string(), Some('n'), Some("level".to_string()))
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .add_argument(
+                ArgumentDef::new_multi_value("command".to_string())
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .order(ArgumentOrder::Permute)
+        .build()
+        .unwrap();
+
+    let app = app_definition.parse_args(&["make", "-n", "5", "all", "--force"]).unwrap();
+    assert_eq!(app.value_of("level"), Some("5"));
+    assert_eq!(app.boolean_flags.get("force"), Some(&true));
+    assert_eq!(app.values_of("command"), Some(vec!["make", "all"]));
+
+    let app = app_definition.parse_args(&["make", "-fn5", "--", "-j4"]).unwrap();
+    assert_eq!(app.value_of("level"), Some("5"));
+    assert_eq!(app.values_of("command"), Some(vec!["make", "-j4"]));
+
+    let app = app_definition.parse_args(&["make", "-n"]);
+    assert_eq!(app.unwrap_err(), "option level requires a value");
+}
+
+#[test]
+fn test_app_with_posix_arguments() {
+    let app_definition = AppDefBuilder::new("nice".to_string())
+        .add_flag(
+                BooleanFlagDefBuilder::new("force".to_string(), Some('f'), Some("force".to_string()))
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .add_option(
+                OptionDef::new_single_value("level".to_string(), Some('n'), Some("level".to_string()))
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .add_argument(
+                ArgumentDef::new_multi_value("command".to_string())
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .order(ArgumentOrder::Posix)
+        .build()
+        .unwrap();
+
+    let app = app_definition.parse_args(&["-f", "-n", "5", "make", "-j4", "--force", "-n"]).unwrap();
+    assert_eq!(app.value_of("level"), Some("5"));
+    assert_eq!(app.boolean_flags.get("force"), Some(&true));
+    assert_eq!(app.values_of("command"), Some(vec!["make", "-j4", "--force", "-n"]));
+
+    let app = app_definition.parse_args(&["make", "-f", "--", "-n"]).unwrap();
+    assert_eq!(app.boolean_flags.get("force"), None);
+    assert_eq!(app.values_of("command"), Some(vec!["make", "-f", "-n"]));
+}
+
+#[test]
+fn test_app_with_posixly_correct_arguments() {
+    let app_definition = AppDefBuilder::new("nice".to_string())
+        .add_flag(
+                BooleanFlagDefBuilder::new("force".to_string(), Some('f'), Some("force".to_string()))
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .add_option(
+                OptionDef::new_single_value("level".to_string(), Some('n'), Some("level".to_string()))
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .add_argument(
+                ArgumentDef::new_multi_value("command".to_string())
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .order(ArgumentOrder::PosixlyCorrect)
+        .build()
+        .unwrap();
+
+    std::env::set_var("POSIXLY_CORRECT", "1");
+    let app = app_definition.parse_args(&["make", "-f"]).unwrap();
+    assert_eq!(app.boolean_flags.get("force"), None);
+    assert_eq!(app.values_of("command"), Some(vec!["make", "-f"]));
+
+    std::env::remove_var("POSIXLY_CORRECT");
+    let app = app_definition.parse_args(&["make", "-f"]).unwrap();
+    assert_eq!(app.boolean_flags.get("force"), Some(&true));
+    assert_eq!(app.values_of("command"), Some(vec!["make"]));
+}
+
+#[test]
+fn test_subcommand_with_own_argument_order() {
+    let app_definition = AppDefBuilder::new("tool".to_string())
+        .subcommand(Some(Box::new(
+                BasicSubCommandDefBuilder::new("exec".to_string())
+                    .add_flag(
+                            BooleanFlagDefBuilder::new("quiet".to_string(), Some('q'), None)
+                                .build()
+                                .unwrap()
+                                .into()
+                    )
+                    .add_argument(
+                            ArgumentDef::new_multi_value("command".to_string())
+                                .build()
+                                .unwrap()
+                                .into()
+                    )
+                    .order(Some(ArgumentOrder::Posix))
+                    .build()
+                    .unwrap()
+                    .into()
+        )))
+        .build()
+        .unwrap();
+
+    let app = app_definition.parse_args(&["exec", "-q", "ls", "-q", "-V"]).unwrap();
+    assert_eq!(app.boolean_flags.get("verbose"), None);
+    let (_, exec) = app.subcommand().unwrap();
+    assert_eq!(exec.boolean_flags.get("quiet"), Some(&true));
+    assert_eq!(exec.values_of("command"), Some(vec!["ls", "-q", "-V"]));
+}
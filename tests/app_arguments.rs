@@ -15,10 +15,10 @@ fn test_app_with_single_required_argument() {
         .unwrap();
 
     let app = app_definition.parse_args(&[]);
-    assert_eq!(app.is_ok(), false);
+    assert!(app.is_err());
 
     let app = app_definition.parse_args(&["red"]).unwrap();
-    assert_eq!(app.single_value_arguments.get("color"), Some(&"red".to_string()));
+    assert_eq!(app.single_value_arguments.get("color"), Some(&"red".into()));
 }
 
 #[test]
@@ -49,21 +49,21 @@ fn test_app_with_three_single_required_arguments() {
         .unwrap();
 
     let app = app_definition.parse_args(&[]);
-    assert_eq!(app.is_ok(), false);
+    assert!(app.is_err());
 
     let app = app_definition.parse_args(&["pizza"]);
-    assert_eq!(app.is_ok(), false);
+    assert!(app.is_err());
 
     let app = app_definition.parse_args(&["pizza", "orange juice"]);
-    assert_eq!(app.is_ok(), false);
+    assert!(app.is_err());
 
 
     let app = app_definition.parse_args(&["pizza", "orange juice", "coleslaw"]).unwrap();
-    assert_eq!(app.single_value_arguments.get("main"), Some(&"pizza".to_string()));
-    assert_eq!(app.single_value_arguments.get("drink"), Some(&"orange juice".to_string()));
-    assert_eq!(app.single_value_arguments.get("side"), Some(&"coleslaw".to_string()));
+    assert_eq!(app.single_value_arguments.get("main"), Some(&"pizza".into()));
+    assert_eq!(app.single_value_arguments.get("drink"), Some(&"orange juice".into()));
+    assert_eq!(app.single_value_arguments.get("side"), Some(&"coleslaw".into()));
 
     let app = app_definition.parse_args(&["pizza", "orange juice", "coleslaw", "salt"]);
-    assert_eq!(app.is_ok(), false);
+    assert!(app.is_err());
 }
 
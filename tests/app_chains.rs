@@ -0,0 +1,129 @@
+use rust_yap::{
+    App, AppDefBuilder, ArgumentDef, BasicSubCommandDefBuilder, OptionDef, SubCommandChainDefBuilder,
+};
+
+
+#[test]
+fn test_app_with_subcommand_chain() {
+    let file = BasicSubCommandDefBuilder::new("file".to_string())
+        .add_option(
+                OptionDef::new_single_value("format".to_string(), Some('f'), Some("format".to_string()))
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .add_argument(
+                ArgumentDef::new_multi_value("paths".to_string())
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .build()
+        .unwrap();
+
+    let app_definition = AppDefBuilder::new("tool".to_string())
+        .subcommand(Some(Box::new(
+                SubCommandChainDefBuilder::new("import".to_string(), file.into())
+                    .add_argument(
+                            ArgumentDef::new_single_value("database".to_string())
+                                .required(true)
+                                .build()
+                                .unwrap()
+                                .into()
+                    )
+                    .max_occurences(Some(3))
+                    .validators(vec![|app: &App| {
+                        let total: usize = app.subcommands.iter().map(|f| f.values_of("paths").unwrap().len()).sum();
+                        if total > 4 {
+                            Err("too many files".to_string())
+                        } else {
+                            Ok(())
+                        }
+                    }])
+                    .build()
+                    .unwrap()
+                    .into()
+        )))
+        .build()
+        .unwrap();
+
+    let app = app_definition.parse_args(&["import", "db", "file", "-f", "csv", "a", "b", "file", "c"]).unwrap();
+    let (name, import) = app.subcommand().unwrap();
+    assert_eq!(name, "import");
+    assert_eq!(import.value_of("database"), Some("db"));
+    assert_eq!(import.subcommands.len(), 2);
+    assert_eq!(import.subcommands[0].name, "file");
+    assert_eq!(import.subcommands[0].value_of("format"), Some("csv"));
+    assert_eq!(import.subcommands[0].values_of("paths"), Some(vec!["a", "b"]));
+    assert_eq!(import.subcommands[1].value_of("format"), None);
+    assert_eq!(import.subcommands[1].values_of("paths"), Some(vec!["c"]));
+
+    let app = app_definition.parse_args(&["import", "db", "file", "a", "file", "b", "file", "c"]).unwrap();
+    assert_eq!(app.subcommand().unwrap().1.subcommands.len(), 3);
+
+    let app = app_definition.parse_args(&["import", "db", "file", "a", "file", "b", "file", "c", "file", "d"]);
+    assert_eq!(app.unwrap_err(), "subcommand file may appear at most 3 time(s), it appeared 4 time(s)");
+    let app = app_definition.parse_args(&["import", "db"]);
+    assert_eq!(app.unwrap_err(), "subcommand file must appear at least 1 time(s), it appeared 0 time(s)");
+    let app = app_definition.parse_args(&["import", "db", "file", "a", "b", "c", "file", "d", "e"]);
+    assert_eq!(app.unwrap_err(), "too many files");
+    let app = app_definition.parse_args(&["import", "db", "file"]);
+    assert_eq!(app.unwrap_err(), "positional argument paths requires at least 1 value(s), 0 given");
+    let app = app_definition.parse_args(&["import", "db", "file", "a", "-f", "csv"]).unwrap();
+    assert_eq!(app.subcommand().unwrap().1.subcommands[0].value_of("format"), Some("csv"));
+}
+
+#[test]
+fn test_app_with_nested_chain_link() {
+    let file = BasicSubCommandDefBuilder::new("file".to_string())
+        .add_option(
+                OptionDef::new_single_value("format".to_string(), Some('f'), Some("format".to_string()))
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .add_argument(
+                ArgumentDef::new_multi_value("paths".to_string())
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .build()
+        .unwrap();
+
+    let app_definition = AppDefBuilder::new("tool".to_string())
+        .subcommand(Some(Box::new(
+                SubCommandChainDefBuilder::new("import".to_string(), file.into())
+                    .add_argument(
+                            ArgumentDef::new_single_value("database".to_string())
+                                .required(true)
+                                .build()
+                                .unwrap()
+                                .into()
+                    )
+                    .max_occurences(Some(3))
+                    .validators(vec![|app: &App| {
+                        let total: usize = app.subcommands.iter().map(|f| f.values_of("paths").unwrap().len()).sum();
+                        if total > 4 {
+                            Err("too many files".to_string())
+                        } else {
+                            Ok(())
+                        }
+                    }])
+                    .build()
+                    .unwrap()
+                    .into()
+        )))
+        .build()
+        .unwrap();
+
+    // a link name that is still needed as a positional value is taken as the value
+    let app = app_definition.parse_args(&["import", "file", "file", "a"]).unwrap();
+    let (_, import) = app.subcommand().unwrap();
+    assert_eq!(import.value_of("database"), Some("file"));
+    assert_eq!(import.subcommands[0].values_of("paths"), Some(vec!["a"]));
+    let app = app_definition.parse_args(&["import", "db", "file", "file", "a"]).unwrap();
+    let (_, import) = app.subcommand().unwrap();
+    assert_eq!(import.subcommands.len(), 1);
+    assert_eq!(import.subcommands[0].values_of("paths"), Some(vec!["file", "a"]));
+}
@@ -0,0 +1,80 @@
+use rust_yap::{
+    AppDefBuilder, ArgumentDef, BasicSubCommandDefBuilder, OptionDef, SubCommandChainsDefBuilder,
+};
+
+
+#[test]
+fn test_app_with_mixed_chain() {
+    let file = BasicSubCommandDefBuilder::new("file".to_string())
+        .add_argument(
+                ArgumentDef::new_single_value("path".to_string())
+                    .required(true)
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .build()
+        .unwrap();
+    let web = BasicSubCommandDefBuilder::new("web".to_string())
+        .add_option(
+                OptionDef::new_single_value("timeout".to_string(), None, Some("timeout".to_string()))
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .add_argument(
+                ArgumentDef::new_single_value("url".to_string())
+                    .required(true)
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .max_occurences(Some(1))
+        .build()
+        .unwrap();
+
+    let app_definition = AppDefBuilder::new("tool".to_string())
+        .subcommand(Some(Box::new(
+                SubCommandChainsDefBuilder::new("import".to_string())
+                    .add_argument(
+                            ArgumentDef::new_single_value("database".to_string())
+                                .required(true)
+                                .build()
+                                .unwrap()
+                                .into()
+                    )
+                    .add_subcommand(file.into())
+                    .add_subcommand(web.into())
+                    .build()
+                    .unwrap()
+                    .into()
+        )))
+        .build()
+        .unwrap();
+
+    let app = app_definition.parse_args(&["import", "db", "file", "PATH", "web", "--timeout", "3", "URL", "file", "PATH2"]).unwrap();
+    let (name, import) = app.subcommand().unwrap();
+    assert_eq!(name, "import");
+    assert_eq!(import.value_of("database"), Some("db"));
+    let links: Vec<&str> = import.subcommands.iter().map(|link| link.name.as_str()).collect();
+    assert_eq!(links, vec!["file", "web", "file"]);
+    assert_eq!(import.subcommands[0].value_of("path"), Some("PATH"));
+    assert_eq!(import.subcommands[1].value_of("timeout"), Some("3"));
+    assert_eq!(import.subcommands[1].value_of("url"), Some("URL"));
+    assert_eq!(import.subcommands[2].value_of("path"), Some("PATH2"));
+    assert_eq!(import.subcommands[2].value_of("timeout"), None);
+
+    let app = app_definition.parse_args(&["import", "db", "web", "URL"]).unwrap();
+    assert_eq!(app.subcommand().unwrap().1.subcommands.len(), 1);
+
+    let app = app_definition.parse_args(&["import", "db", "web", "a", "file", "b", "web", "c"]);
+    assert_eq!(app.unwrap_err(), "subcommand web may appear at most 1 time(s), it appeared 2 time(s)");
+    let app = app_definition.parse_args(&["import", "db"]);
+    assert_eq!(app.unwrap_err(), "subcommand file, web must appear at least 1 time(s), it appeared 0 time(s)");
+    let app = app_definition.parse_args(&["import", "db", "file", "a", "ftp", "b"]);
+    assert_eq!(app.unwrap_err(), "unexpected argument: ftp");
+    let app = app_definition.parse_args(&["import", "db", "ftp", "b"]);
+    assert_eq!(app.unwrap_err(), "unknown subcommand: ftp, expected one of: file, web");
+    let app = app_definition.parse_args(&["import", "db", "file", "web"]).unwrap();
+    assert_eq!(app.subcommand().unwrap().1.subcommands[0].value_of("path"), Some("web"));
+}
@@ -0,0 +1,340 @@
+#![cfg(feature = "config")]
+
+use std::fs;
+use std::path::PathBuf;
+
+use indexmap::IndexMap;
+use rust_yap::{AppDefBuilder, BasicSubCommandDefBuilder, BooleanFlagDefBuilder, OptionDef, ValueSource};
+
+
+fn test_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("rust_yap_{}", name));
+    fs::create_dir_all(dir.join("sync")).unwrap();
+    dir
+}
+
+
+#[test]
+fn test_app_with_config_file() {
+    let dir = test_dir("config_file");
+    fs::write(
+        dir.join("sync").join("config.ini"),
+        "# defaults\nport = 9000\nexclude = a\nexclude = \"b c\"\ndebug = yes\n\n[remote]\nname = origin\n",
+    ).unwrap();
+    let mut environment = IndexMap::new();
+    environment.insert("XDG_CONFIG_HOME".to_string(), dir.display().to_string());
+    environment.insert("XDG_CONFIG_DIRS".to_string(), dir.join("none").display().to_string());
+    let app_definition = AppDefBuilder::new("sync".to_string())
+        .add_flag(
+                BooleanFlagDefBuilder::new("debug".to_string(), Some('d'), Some("debug".to_string()))
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .add_option(
+                OptionDef::new_single_value("port".to_string(), Some('p'), Some("port".to_string()))
+                    .env(Some("SYNC_PORT".to_string()))
+                    .default_value(Some("8080".to_string()))
+                    .validators(vec![|value| match value.parse::<u16>() {
+                        Ok(_) => Ok(()),
+                        Err(_) => Err(format!("invalid port: {}", value)),
+                    }])
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .add_option(
+                OptionDef::new_multi_value("exclude".to_string(), Some('e'), Some("exclude".to_string()))
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .subcommand(Some(Box::new(
+                BasicSubCommandDefBuilder::new("remote".to_string())
+                    .add_option(
+                            OptionDef::new_single_value("name".to_string(), Some('n'), Some("name".to_string()))
+                                .build()
+                                .unwrap()
+                                .into()
+                    )
+                    .build()
+                    .unwrap()
+                    .into()
+        )))
+        .add_config_file()
+        .environment(Some(environment))
+        .build()
+        .unwrap();
+
+    let app = app_definition.parse_args(&[]).unwrap();
+    assert_eq!(app.value_of("port"), Some("9000"));
+    assert_eq!(app.values_of("exclude"), Some(vec!["a", "b c"]));
+    assert_eq!(app.boolean_flags.get("debug"), Some(&true));
+    assert_eq!(app.source_of("port"), Some(ValueSource::Config));
+    assert_eq!(app.source_of("debug"), Some(ValueSource::Config));
+
+    let app = app_definition.parse_args(&["-p", "1000", "-e", "x", "remote"]).unwrap();
+    assert_eq!(app.value_of("port"), Some("1000"));
+    assert_eq!(app.values_of("exclude"), Some(vec!["x"]));
+    assert_eq!(app.source_of("port"), Some(ValueSource::CommandLine));
+    let (_, remote) = app.subcommand().unwrap();
+    assert_eq!(remote.value_of("name"), Some("origin"));
+    assert_eq!(remote.source_of("name"), Some(ValueSource::Config));
+
+    let app = app_definition.parse_args(&["remote", "-n", "upstream"]).unwrap();
+    assert_eq!(app.subcommand().unwrap().1.value_of("name"), Some("upstream"));
+
+    let mut environment = IndexMap::new();
+    environment.insert("XDG_CONFIG_HOME".to_string(), dir.display().to_string());
+    environment.insert("XDG_CONFIG_DIRS".to_string(), dir.join("none").display().to_string());
+    environment.insert("SYNC_PORT".to_string(), "7000".to_string());
+    let app_definition = AppDefBuilder::new("sync".to_string())
+        .add_flag(
+                BooleanFlagDefBuilder::new("debug".to_string(), Some('d'), Some("debug".to_string()))
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .add_option(
+                OptionDef::new_single_value("port".to_string(), Some('p'), Some("port".to_string()))
+                    .env(Some("SYNC_PORT".to_string()))
+                    .default_value(Some("8080".to_string()))
+                    .validators(vec![|value| match value.parse::<u16>() {
+                        Ok(_) => Ok(()),
+                        Err(_) => Err(format!("invalid port: {}", value)),
+                    }])
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .add_option(
+                OptionDef::new_multi_value("exclude".to_string(), Some('e'), Some("exclude".to_string()))
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .subcommand(Some(Box::new(
+                BasicSubCommandDefBuilder::new("remote".to_string())
+                    .add_option(
+                            OptionDef::new_single_value("name".to_string(), Some('n'), Some("name".to_string()))
+                                .build()
+                                .unwrap()
+                                .into()
+                    )
+                    .build()
+                    .unwrap()
+                    .into()
+        )))
+        .add_config_file()
+        .environment(Some(environment))
+        .build()
+        .unwrap();
+
+    let app = app_definition.parse_args(&[]).unwrap();
+    assert_eq!(app.value_of("port"), Some("7000"));
+    assert_eq!(app.source_of("port"), Some(ValueSource::Environment));
+    let app = app_definition.parse_args(&["remote"]).unwrap();
+    assert_eq!(app.value_of("port"), Some("7000"));
+    assert_eq!(app.source_of("port"), Some(ValueSource::Environment));
+    assert_eq!(app.subcommand().unwrap().1.source_of("name"), Some(ValueSource::Config));
+}
+
+#[test]
+fn test_app_with_explicit_config_file() {
+    let dir = test_dir("explicit_config_file");
+    fs::write(dir.join("sync").join("config.ini"), "port = 9000\n").unwrap();
+    fs::write(dir.join("other.ini"), "port = 9001\n[remote]\nname = backup\n").unwrap();
+    let other = dir.join("other.ini").display().to_string();
+    let mut environment = IndexMap::new();
+    environment.insert("XDG_CONFIG_HOME".to_string(), dir.display().to_string());
+    environment.insert("XDG_CONFIG_DIRS".to_string(), dir.join("none").display().to_string());
+    let app_definition = AppDefBuilder::new("sync".to_string())
+        .add_flag(
+                BooleanFlagDefBuilder::new("debug".to_string(), Some('d'), Some("debug".to_string()))
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .add_option(
+                OptionDef::new_single_value("port".to_string(), Some('p'), Some("port".to_string()))
+                    .env(Some("SYNC_PORT".to_string()))
+                    .default_value(Some("8080".to_string()))
+                    .validators(vec![|value| match value.parse::<u16>() {
+                        Ok(_) => Ok(()),
+                        Err(_) => Err(format!("invalid port: {}", value)),
+                    }])
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .add_option(
+                OptionDef::new_multi_value("exclude".to_string(), Some('e'), Some("exclude".to_string()))
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .subcommand(Some(Box::new(
+                BasicSubCommandDefBuilder::new("remote".to_string())
+                    .add_option(
+                            OptionDef::new_single_value("name".to_string(), Some('n'), Some("name".to_string()))
+                                .build()
+                                .unwrap()
+                                .into()
+                    )
+                    .build()
+                    .unwrap()
+                    .into()
+        )))
+        .add_config_file()
+        .environment(Some(environment))
+        .build()
+        .unwrap();
+
+    let app = app_definition.parse_args(&["--config", &other, "remote"]).unwrap();
+    assert_eq!(app.value_of("port"), Some("9001"));
+    assert_eq!(app.subcommand().unwrap().1.value_of("name"), Some("backup"));
+
+    let missing = dir.join("missing.ini").display().to_string();
+    let error = app_definition.parse_args(&["--config", &missing]).unwrap_err();
+    assert!(error.starts_with(&format!("cannot read config file {}: ", missing)));
+}
+
+#[test]
+fn test_app_without_config_file() {
+    let dir = test_dir("no_config_file");
+    let _ = fs::remove_file(dir.join("sync").join("config.ini"));
+
+    let mut environment = IndexMap::new();
+    environment.insert("XDG_CONFIG_HOME".to_string(), dir.display().to_string());
+    environment.insert("XDG_CONFIG_DIRS".to_string(), dir.join("none").display().to_string());
+    let app_definition = AppDefBuilder::new("sync".to_string())
+        .add_flag(
+                BooleanFlagDefBuilder::new("debug".to_string(), Some('d'), Some("debug".to_string()))
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .add_option(
+                OptionDef::new_single_value("port".to_string(), Some('p'), Some("port".to_string()))
+                    .env(Some("SYNC_PORT".to_string()))
+                    .default_value(Some("8080".to_string()))
+                    .validators(vec![|value| match value.parse::<u16>() {
+                        Ok(_) => Ok(()),
+                        Err(_) => Err(format!("invalid port: {}", value)),
+                    }])
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .add_option(
+                OptionDef::new_multi_value("exclude".to_string(), Some('e'), Some("exclude".to_string()))
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .subcommand(Some(Box::new(
+                BasicSubCommandDefBuilder::new("remote".to_string())
+                    .add_option(
+                            OptionDef::new_single_value("name".to_string(), Some('n'), Some("name".to_string()))
+                                .build()
+                                .unwrap()
+                                .into()
+                    )
+                    .build()
+                    .unwrap()
+                    .into()
+        )))
+        .add_config_file()
+        .environment(Some(environment))
+        .build()
+        .unwrap();
+
+    let app = app_definition.parse_args(&["remote"]).unwrap();
+    assert_eq!(app.value_of("port"), Some("8080"));
+    assert_eq!(app.source_of("port"), Some(ValueSource::Default));
+    assert_eq!(app.subcommand().unwrap().1.value_of("name"), None);
+}
+
+#[test]
+fn test_app_with_invalid_config_file() {
+    let dir = test_dir("invalid_config_file");
+    let path = dir.join("sync").join("config.ini");
+    let mut environment = IndexMap::new();
+    environment.insert("XDG_CONFIG_HOME".to_string(), dir.display().to_string());
+    environment.insert("XDG_CONFIG_DIRS".to_string(), dir.join("none").display().to_string());
+    let app_definition = AppDefBuilder::new("sync".to_string())
+        .add_flag(
+                BooleanFlagDefBuilder::new("debug".to_string(), Some('d'), Some("debug".to_string()))
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .add_option(
+                OptionDef::new_single_value("port".to_string(), Some('p'), Some("port".to_string()))
+                    .env(Some("SYNC_PORT".to_string()))
+                    .default_value(Some("8080".to_string()))
+                    .validators(vec![|value| match value.parse::<u16>() {
+                        Ok(_) => Ok(()),
+                        Err(_) => Err(format!("invalid port: {}", value)),
+                    }])
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .add_option(
+                OptionDef::new_multi_value("exclude".to_string(), Some('e'), Some("exclude".to_string()))
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .subcommand(Some(Box::new(
+                BasicSubCommandDefBuilder::new("remote".to_string())
+                    .add_option(
+                            OptionDef::new_single_value("name".to_string(), Some('n'), Some("name".to_string()))
+                                .build()
+                                .unwrap()
+                                .into()
+                    )
+                    .build()
+                    .unwrap()
+                    .into()
+        )))
+        .add_config_file()
+        .environment(Some(environment))
+        .build()
+        .unwrap();
+
+    fs::write(&path, "port = 9000\ncolor = red\n[remote]\nnmae = origin\n").unwrap();
+    let (_, errors) = app_definition.parse_args_recover(&[]);
+    assert_eq!(
+        errors,
+        vec![
+            format!("{}:2: unknown key: color", path.display()),
+            format!("{}:4: unknown key: nmae", path.display()),
+        ]
+    );
+
+    fs::write(&path, "[local]\n").unwrap();
+    let error = app_definition.parse_args(&[]).unwrap_err();
+    assert_eq!(error, format!("{}:1: unknown section: local", path.display()));
+
+    fs::write(&path, "port = high\n").unwrap();
+    assert_eq!(app_definition.parse_args(&[]).unwrap_err(), "invalid port: high");
+
+    fs::write(&path, "port = 1\nport = 2\n").unwrap();
+    let error = app_definition.parse_args(&[]).unwrap_err();
+    assert_eq!(error, format!("{}:2: option port may appear only once", path.display()));
+
+    fs::write(&path, "debug = maybe\n").unwrap();
+    let error = app_definition.parse_args(&[]).unwrap_err();
+    assert_eq!(error, format!("{}:1: flag debug expects a boolean, got: maybe", path.display()));
+
+    fs::write(&path, "debug\n").unwrap();
+    let error = app_definition.parse_args(&[]).unwrap_err();
+    assert_eq!(error, format!("{}:1: expected key = value", path.display()));
+
+    fs::write(&path, "config = other.ini
+").unwrap();
+    let error = app_definition.parse_args(&["remote"]).unwrap_err();
+    assert_eq!(error, format!("{}:1: config cannot be set in the configuration file", path.display()));
+}
@@ -0,0 +1,188 @@
+use rust_yap::{AppDefBuilder, ArgumentDef, BooleanFlagDefBuilder, OptionDef};
+
+
+#[test]
+fn test_app_with_default_values() {
+    let app_definition = AppDefBuilder::new("serve".to_string())
+        .add_flag(
+                BooleanFlagDefBuilder::new("tls".to_string(), None, Some("tls".to_string()))
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .add_option(
+                OptionDef::new_single_value("port".to_string(), Some('p'), Some("port".to_string()))
+                    .help(Some("port to listen on".to_string()))
+                    .default_value(Some("80".to_string()))
+                    .default_if("tls".to_string(), "443".to_string())
+                    .validators(vec![|value| match value.parse::<u16>() {
+                        Ok(_) => Ok(()),
+                        Err(_) => Err(format!("invalid port: {}", value)),
+                    }])
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .add_option(
+                OptionDef::new_multi_value("index".to_string(), None, Some("index".to_string()))
+                    .default_values(vec!["index.html".to_string(), "index.htm".to_string()])
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .add_argument(
+                ArgumentDef::new_single_value("root".to_string())
+                    .default_value(Some(".".to_string()))
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .add_argument(
+                ArgumentDef::new_multi_value("hosts".to_string())
+                    .min_occurences(0)
+                    .default_if("tls".to_string(), vec!["localhost".to_string()])
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .build()
+        .unwrap();
+
+    let app = app_definition.parse_args(&[]).unwrap();
+    assert_eq!(app.value_of("port"), Some("80"));
+    assert_eq!(app.values_of("index"), Some(vec!["index.html", "index.htm"]));
+    assert_eq!(app.value_of("root"), Some("."));
+    assert_eq!(app.values_of("hosts"), None);
+
+    let app = app_definition.parse_args(&["--tls"]).unwrap();
+    assert_eq!(app.value_of("port"), Some("443"));
+    assert_eq!(app.values_of("hosts"), Some(vec!["localhost"]));
+
+    let app = app_definition.parse_args(&["--tls", "-p", "8443", "--index", "main.html", "/srv", "example.com"]).unwrap();
+    assert_eq!(app.value_of("port"), Some("8443"));
+    assert_eq!(app.values_of("index"), Some(vec!["main.html"]));
+    assert_eq!(app.value_of("root"), Some("/srv"));
+    assert_eq!(app.values_of("hosts"), Some(vec!["example.com"]));
+}
+
+#[test]
+fn test_default_values_are_validated() {
+    let app_definition = AppDefBuilder::new("serve".to_string())
+        .add_option(
+                OptionDef::new_single_value("port".to_string(), Some('p'), Some("port".to_string()))
+                    .default_value(Some("http".to_string()))
+                    .validators(vec![|value| match value.parse::<u16>() {
+                        Ok(_) => Ok(()),
+                        Err(_) => Err(format!("invalid port: {}", value)),
+                    }])
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .build()
+        .unwrap();
+
+    let app = app_definition.parse_args(&[]);
+    assert_eq!(app.unwrap_err(), "invalid port: http");
+    let app = app_definition.parse_args(&["-p", "80"]).unwrap();
+    assert_eq!(app.value_of("port"), Some("80"));
+}
+
+#[test]
+fn test_conditional_defaults_only_check_presence() {
+    let app_definition = AppDefBuilder::new("serve".to_string())
+        .add_option(
+                OptionDef::new_single_value("cert".to_string(), None, Some("cert".to_string()))
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .add_option(
+                OptionDef::new_single_value("port".to_string(), Some('p'), Some("port".to_string()))
+                    .default_value(Some("80".to_string()))
+                    .default_if("cert".to_string(), "443".to_string())
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .add_argument(
+                ArgumentDef::new_single_value("root".to_string())
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .add_argument(
+                ArgumentDef::new_multi_value("hosts".to_string())
+                    .min_occurences(0)
+                    .default_if("root".to_string(), vec!["localhost".to_string()])
+                    .default_if("cert".to_string(), vec!["example.com".to_string()])
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .build()
+        .unwrap();
+
+    let app = app_definition.parse_args(&["--cert", "none"]).unwrap();
+    assert_eq!(app.value_of("port"), Some("443"));
+    assert_eq!(app.values_of("hosts"), Some(vec!["example.com"]));
+    let app = app_definition.parse_args(&["--cert", "", "/srv"]).unwrap();
+    assert_eq!(app.value_of("port"), Some("443"));
+    assert_eq!(app.values_of("hosts"), Some(vec!["localhost"]));
+    let app = app_definition.parse_args(&["/srv"]).unwrap();
+    assert_eq!(app.value_of("port"), Some("80"));
+    assert_eq!(app.values_of("hosts"), Some(vec!["localhost"]));
+}
+
+#[test]
+fn test_usage_with_default_values() {
+    let app_definition = AppDefBuilder::new("serve".to_string())
+        .add_flag(
+                BooleanFlagDefBuilder::new("tls".to_string(), None, Some("tls".to_string()))
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .add_option(
+                OptionDef::new_single_value("port".to_string(), Some('p'), Some("port".to_string()))
+                    .help(Some("port to listen on".to_string()))
+                    .default_value(Some("80".to_string()))
+                    .defaults_if(vec![("tls".to_string(), "443".to_string())])
+                    .validators(vec![|value| match value.parse::<u16>() {
+                        Ok(_) => Ok(()),
+                        Err(_) => Err(format!("invalid port: {}", value)),
+                    }])
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .add_option(
+                OptionDef::new_multi_value("index".to_string(), None, Some("index".to_string()))
+                    .default_values(vec!["index.html".to_string(), "index.htm".to_string()])
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .add_argument(
+                ArgumentDef::new_single_value("root".to_string())
+                    .default_value(Some(".".to_string()))
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .add_argument(
+                ArgumentDef::new_multi_value("hosts".to_string())
+                    .min_occurences(0)
+                    .defaults_if(vec![("tls".to_string(), vec!["localhost".to_string()])])
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .build()
+        .unwrap();
+
+    let usage = app_definition.get_subcommand_usage(&[]).unwrap();
+    assert!(usage.contains("\nARGUMENTS:\n    root [default: .]\n    hosts\n"));
+    assert!(usage.contains("    -p, --port port to listen on [default: 80]\n"));
+    assert!(usage.contains("    --index [INDEX] [...] [default: index.html, index.htm]\n"));
+}
@@ -0,0 +1,189 @@
+use indexmap::IndexMap;
+use rust_yap::{AppDefBuilder, BooleanFlagDefBuilder, OptionDef, ValueSource};
+
+
+#[test]
+fn test_app_with_env_fallback() {
+    let mut environment = IndexMap::new();
+    environment.insert("APP_DEBUG".to_string(), "Yes".to_string());
+    environment.insert("APP_COLOR".to_string(), "red".to_string());
+    environment.insert("APP_PEERS".to_string(), "a:1,b:2".to_string());
+    let app_definition = AppDefBuilder::new("server".to_string())
+        .add_flag(
+                BooleanFlagDefBuilder::new("debug".to_string(), Some('d'), Some("debug".to_string()))
+                    .env(Some("APP_DEBUG".to_string()))
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .add_option(
+                OptionDef::new_single_value("color".to_string(), Some('c'), Some("color".to_string()))
+                    .help(Some("color of the output".to_string()))
+                    .env(Some("APP_COLOR".to_string()))
+                    .default_value(Some("auto".to_string()))
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .add_option(
+                OptionDef::new_multi_value("peer".to_string(), Some('p'), Some("peer".to_string()))
+                    .env(Some("APP_PEERS".to_string()))
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .environment(Some(environment))
+        .build()
+        .unwrap();
+
+    let app = app_definition.parse_args(&[]).unwrap();
+    assert_eq!(app.boolean_flags.get("debug"), Some(&true));
+    assert_eq!(app.value_of("color"), Some("red"));
+    assert_eq!(app.values_of("peer"), Some(vec!["a:1", "b:2"]));
+    assert_eq!(app.source_of("color"), Some(ValueSource::Environment));
+    assert_eq!(app.source_of("debug"), Some(ValueSource::Environment));
+
+    let app = app_definition.parse_args(&["-c", "blue", "--peer", "c:3"]).unwrap();
+    assert_eq!(app.value_of("color"), Some("blue"));
+    assert_eq!(app.values_of("peer"), Some(vec!["c:3"]));
+    assert_eq!(app.source_of("color"), Some(ValueSource::CommandLine));
+    assert_eq!(app.source_of("verbose"), None);
+}
+
+#[test]
+fn test_app_without_env() {
+    let mut environment = IndexMap::new();
+    environment.insert("APP_DEBUG".to_string(), "off".to_string());
+    let app_definition = AppDefBuilder::new("server".to_string())
+        .add_flag(
+                BooleanFlagDefBuilder::new("debug".to_string(), Some('d'), Some("debug".to_string()))
+                    .env(Some("APP_DEBUG".to_string()))
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .add_option(
+                OptionDef::new_single_value("color".to_string(), Some('c'), Some("color".to_string()))
+                    .help(Some("color of the output".to_string()))
+                    .env(Some("APP_COLOR".to_string()))
+                    .default_value(Some("auto".to_string()))
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .add_option(
+                OptionDef::new_multi_value("peer".to_string(), Some('p'), Some("peer".to_string()))
+                    .env(Some("APP_PEERS".to_string()))
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .environment(Some(environment))
+        .build()
+        .unwrap();
+
+    let app = app_definition.parse_args(&[]).unwrap();
+    assert_eq!(app.boolean_flags.get("debug"), Some(&false));
+    assert_eq!(app.value_of("color"), Some("auto"));
+    assert_eq!(app.source_of("color"), Some(ValueSource::Default));
+    assert_eq!(app.values_of("peer"), None);
+
+    let mut environment = IndexMap::new();
+    environment.insert("APP_DEBUG".to_string(), "maybe".to_string());
+    let app_definition = AppDefBuilder::new("server".to_string())
+        .add_flag(
+                BooleanFlagDefBuilder::new("debug".to_string(), Some('d'), Some("debug".to_string()))
+                    .env(Some("APP_DEBUG".to_string()))
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .add_option(
+                OptionDef::new_single_value("color".to_string(), Some('c'), Some("color".to_string()))
+                    .help(Some("color of the output".to_string()))
+                    .env(Some("APP_COLOR".to_string()))
+                    .default_value(Some("auto".to_string()))
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .add_option(
+                OptionDef::new_multi_value("peer".to_string(), Some('p'), Some("peer".to_string()))
+                    .env(Some("APP_PEERS".to_string()))
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .environment(Some(environment))
+        .build()
+        .unwrap();
+
+    let app = app_definition.parse_args(&[]);
+    assert_eq!(app.unwrap_err(), "flag debug expects a boolean in APP_DEBUG, got: maybe");
+    let app = app_definition.parse_args(&["-d"]).unwrap();
+    assert_eq!(app.boolean_flags.get("debug"), Some(&true));
+}
+
+#[test]
+fn test_usage_with_env() {
+    let app_definition = AppDefBuilder::new("server".to_string())
+        .add_flag(
+                BooleanFlagDefBuilder::new("debug".to_string(), Some('d'), Some("debug".to_string()))
+                    .env(Some("APP_DEBUG".to_string()))
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .add_option(
+                OptionDef::new_single_value("color".to_string(), Some('c'), Some("color".to_string()))
+                    .help(Some("color of the output".to_string()))
+                    .env(Some("APP_COLOR".to_string()))
+                    .default_value(Some("auto".to_string()))
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .add_option(
+                OptionDef::new_multi_value("peer".to_string(), Some('p'), Some("peer".to_string()))
+                    .env(Some("APP_PEERS".to_string()))
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .environment(Some(IndexMap::new()))
+        .build()
+        .unwrap();
+
+    let usage = app_definition.get_subcommand_usage(&[]).unwrap();
+    assert!(usage.contains("    -d, --debug [env: APP_DEBUG]\n"));
+    assert!(usage.contains("    -c, --color color of the output [env: APP_COLOR] [default: auto]\n"));
+    assert!(usage.contains("    -p, --peer [PEER] [...] [env: APP_PEERS]\n"));
+}
+
+#[test]
+fn test_usage_with_env_only_items() {
+    let app_definition = AppDefBuilder::new("server".to_string())
+        .add_flag(
+                BooleanFlagDefBuilder::new("debug".to_string(), None, None)
+                    .env(Some("APP_DEBUG".to_string()))
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .add_option(
+                OptionDef::new_single_value("token".to_string(), None, None)
+                    .help(Some("access token".to_string()))
+                    .env(Some("APP_TOKEN".to_string()))
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .environment(Some(IndexMap::new()))
+        .build()
+        .unwrap();
+
+    let usage = app_definition.get_subcommand_usage(&[]).unwrap();
+    assert!(usage.contains("    [env: APP_DEBUG]\n"));
+    assert!(usage.contains("    [env: APP_TOKEN] access token\n"));
+    app_definition.print_usage();
+}
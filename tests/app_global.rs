@@ -0,0 +1,208 @@
+use rust_yap::{
+    AppDefBuilder, ArgumentDef, BasicSubCommandDefBuilder, BooleanFlagDefBuilder, CountedFlagDefBuilder,
+    OptionDef, SubCommandChainDefBuilder,
+};
+
+
+#[test]
+fn test_app_with_global_flags() {
+    let app_definition = AppDefBuilder::new("import".to_string())
+        .add_flag(
+                CountedFlagDefBuilder::new("debug".to_string(), Some('d'), Some("debug".to_string()))
+                    .global(true)
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .add_flag(
+                BooleanFlagDefBuilder::new("dry-run".to_string(), Some('n'), Some("dry-run".to_string()))
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .add_option(
+                OptionDef::new_single_value("config".to_string(), Some('c'), Some("config".to_string()))
+                    .help(Some("configuration file".to_string()))
+                    .global(true)
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .subcommand(Some(Box::new(
+                SubCommandChainDefBuilder::new(
+                    "source".to_string(),
+                    BasicSubCommandDefBuilder::new("file".to_string())
+                        .help(Some("read a local file".to_string()))
+                        .add_flag(
+                                BooleanFlagDefBuilder::new("force".to_string(), Some('f'), Some("force".to_string()))
+                                    .build()
+                                    .unwrap()
+                                    .into()
+                        )
+                        .add_argument(
+                                ArgumentDef::new_single_value("path".to_string())
+                                    .build()
+                                    .unwrap()
+                                    .into()
+                        )
+                        .build()
+                        .unwrap()
+                        .into()
+                )
+                    .build()
+                    .unwrap()
+                    .into()
+        )))
+        .build()
+        .unwrap();
+
+    let app = app_definition.parse_args(&["-d", "source", "file", "a", "-V", "file", "b", "--debug", "-f"]).unwrap();
+    assert_eq!(app.boolean_flags.get("verbose"), Some(&true));
+    assert_eq!(app.counted_flags.get("debug"), Some(&2));
+    let (_, source) = app.subcommand().unwrap();
+    assert!(source.subcommands[0].boolean_flags.get("verbose").is_none());
+    assert_eq!(source.subcommands[1].boolean_flags.get("force"), Some(&true));
+    assert!(source.subcommands[1].counted_flags.is_empty());
+
+    let app = app_definition.parse_args(&["-V", "source", "file", "a", "--verbose"]);
+    assert_eq!(app.unwrap_err(), "flag verbose may appear only once");
+    let app = app_definition.parse_args(&["source", "file", "a", "-n"]);
+    assert_eq!(app.unwrap_err(), "unknown option: -n");
+}
+
+#[test]
+fn test_app_with_global_options() {
+    let app_definition = AppDefBuilder::new("import".to_string())
+        .add_flag(
+                CountedFlagDefBuilder::new("debug".to_string(), Some('d'), Some("debug".to_string()))
+                    .global(true)
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .add_flag(
+                BooleanFlagDefBuilder::new("dry-run".to_string(), Some('n'), Some("dry-run".to_string()))
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .add_option(
+                OptionDef::new_single_value("config".to_string(), Some('c'), Some("config".to_string()))
+                    .help(Some("configuration file".to_string()))
+                    .global(true)
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .subcommand(Some(Box::new(
+                SubCommandChainDefBuilder::new(
+                    "source".to_string(),
+                    BasicSubCommandDefBuilder::new("file".to_string())
+                        .help(Some("read a local file".to_string()))
+                        .add_flag(
+                                BooleanFlagDefBuilder::new("force".to_string(), Some('f'), Some("force".to_string()))
+                                    .build()
+                                    .unwrap()
+                                    .into()
+                        )
+                        .add_argument(
+                                ArgumentDef::new_single_value("path".to_string())
+                                    .build()
+                                    .unwrap()
+                                    .into()
+                        )
+                        .build()
+                        .unwrap()
+                        .into()
+                )
+                    .build()
+                    .unwrap()
+                    .into()
+        )))
+        .build()
+        .unwrap();
+
+    let app = app_definition.parse_args(&["source", "file", "a", "--config=x.ini", "file", "b"]).unwrap();
+    assert_eq!(app.value_of("config"), Some("x.ini"));
+    let app = app_definition.parse_args(&["source", "file", "-c", "x.ini", "a"]).unwrap();
+    assert_eq!(app.value_of("config"), Some("x.ini"));
+
+    let app = app_definition.parse_args(&["-c", "x.ini", "source", "file", "a", "-c", "y.ini"]);
+    assert_eq!(app.unwrap_err(), "option config may appear only once");
+}
+
+#[test]
+fn test_subcommand_usage_with_global_flags() {
+    let app_definition = AppDefBuilder::new("import".to_string())
+        .add_flag(
+                CountedFlagDefBuilder::new("debug".to_string(), Some('d'), Some("debug".to_string()))
+                    .global(true)
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .add_flag(
+                BooleanFlagDefBuilder::new("dry-run".to_string(), Some('n'), Some("dry-run".to_string()))
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .add_option(
+                OptionDef::new_single_value("config".to_string(), Some('c'), Some("config".to_string()))
+                    .help(Some("configuration file".to_string()))
+                    .global(true)
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .subcommand(Some(Box::new(
+                SubCommandChainDefBuilder::new(
+                    "source".to_string(),
+                    BasicSubCommandDefBuilder::new("file".to_string())
+                        .help(Some("read a local file".to_string()))
+                        .add_flag(
+                                BooleanFlagDefBuilder::new("force".to_string(), Some('f'), Some("force".to_string()))
+                                    .build()
+                                    .unwrap()
+                                    .into()
+                        )
+                        .add_argument(
+                                ArgumentDef::new_single_value("path".to_string())
+                                    .build()
+                                    .unwrap()
+                                    .into()
+                        )
+                        .build()
+                        .unwrap()
+                        .into()
+                )
+                    .build()
+                    .unwrap()
+                    .into()
+        )))
+        .build()
+        .unwrap();
+
+    assert_eq!(
+        app_definition.get_subcommand_usage(&["source", "file"]).unwrap(),
+        "read a local file
+
+USAGE:
+    import source file [FLAGS] [OPTIONS] [path]
+
+ARGUMENTS:
+    path
+
+FLAGS:
+    -f, --force
+
+GLOBAL FLAGS:
+    -V, --verbose
+    -d, --debug [...]
+
+GLOBAL OPTIONS:
+    -c, --config configuration file
+"
+    );
+    assert!(app_definition.get_subcommand_usage(&["file"]).is_none());
+}
@@ -0,0 +1,97 @@
+use rust_yap::{AppDefBuilder, ArgumentDef, BooleanFlagDefBuilder, OptionDef};
+
+
+#[test]
+fn test_app_with_negative_numbers() {
+    let app_definition = AppDefBuilder::new("abc".to_string())
+        .add_option(
+                OptionDef::new_single_value("offset".to_string(), Some('o'), Some("offset".to_string()))
+                    .allow_negative_numbers(true)
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .add_argument(
+                ArgumentDef::new_single_value("scale".to_string())
+                    .allow_negative_numbers(true)
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .build()
+        .unwrap();
+
+    let app = app_definition.parse_args(&["--offset", "-5", "-3.2"]).unwrap();
+    assert_eq!(app.value_of("offset"), Some("-5"));
+    assert_eq!(app.value_of("scale"), Some("-3.2"));
+
+    let app = app_definition.parse_args(&["-V", "-o", "-5"]).unwrap();
+    assert_eq!(app.boolean_flags.get("verbose"), Some(&true));
+    assert_eq!(app.value_of("offset"), Some("-5"));
+
+    let app = app_definition.parse_args(&["--offset", "-x"]);
+    assert!(app.is_err());
+}
+
+#[test]
+fn test_app_with_hyphen_values() {
+    let app_definition = AppDefBuilder::new("abc".to_string())
+        .add_option(
+                OptionDef::new_single_value("pattern".to_string(), Some('p'), Some("pattern".to_string()))
+                    .allow_hyphen_values(true)
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .add_option(
+                OptionDef::new_single_value("offset".to_string(), None, Some("offset".to_string()))
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .build()
+        .unwrap();
+
+    let app = app_definition.parse_args(&["--pattern", "--verbose", "-V"]).unwrap();
+    assert_eq!(app.value_of("pattern"), Some("--verbose"));
+    assert_eq!(app.boolean_flags.get("verbose"), Some(&true));
+
+    let app = app_definition.parse_args(&["-p", "-x"]).unwrap();
+    assert_eq!(app.value_of("pattern"), Some("-x"));
+
+    let app = app_definition.parse_args(&["--offset", "-5"]);
+    assert!(app.is_err());
+}
+
+#[test]
+fn test_app_with_flags_before_hyphen_positional() {
+    let app_definition = AppDefBuilder::new("abc".to_string())
+        .add_flag(
+                BooleanFlagDefBuilder::new("force".to_string(), Some('f'), Some("force".to_string()))
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .add_argument(
+                ArgumentDef::new_multi_value("cmd".to_string())
+                    .allow_hyphen_values(true)
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .build()
+        .unwrap();
+
+    let app = app_definition.parse_args(&["--force", "x", "-y"]).unwrap();
+    assert_eq!(app.boolean_flags.get("force"), Some(&true));
+    assert_eq!(app.values_of("cmd"), Some(vec!["x", "-y"]));
+
+    let app = app_definition.parse_args(&["-f", "-V", "x", "--force"]).unwrap();
+    assert_eq!(app.boolean_flags.get("force"), Some(&true));
+    assert_eq!(app.boolean_flags.get("verbose"), Some(&true));
+    assert_eq!(app.values_of("cmd"), Some(vec!["x", "--force"]));
+
+    let app = app_definition.parse_args(&["-y", "--force"]).unwrap();
+    assert_eq!(app.boolean_flags.get("force"), None);
+    assert_eq!(app.values_of("cmd"), Some(vec!["-y", "--force"]));
+}
@@ -0,0 +1,181 @@
+use rust_yap::{AppDefBuilder, BooleanFlagDefBuilder, Matching, OptionDef};
+
+
+#[test]
+fn test_app_with_matching_policy() {
+    let app_definition = AppDefBuilder::new("deploy".to_string())
+        .matching(Matching { ignore_case: true, ignore_separators: true })
+        .add_flag(
+                BooleanFlagDefBuilder::new("dry-run".to_string(), None, Some("dry-run".to_string()))
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .add_option(
+                OptionDef::new_single_value("target_dir".to_string(), None, Some("target_dir".to_string()))
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .build()
+        .unwrap();
+
+    for arg in &["--dry-run", "--Dry-Run", "--dry_run", "--DRY_RUN"] {
+        let app = app_definition.parse_args(&[arg]).unwrap();
+        assert_eq!(app.boolean_flags.get("dry-run"), Some(&true));
+        assert!(app.abbreviations.is_empty());
+    }
+    let app = app_definition.parse_args(&["--Target-Dir", "out"]).unwrap();
+    assert_eq!(app.value_of("target_dir"), Some("out"));
+
+    let app = app_definition.parse_args(&["--dry"]);
+    assert_eq!(app.unwrap_err(), "unknown option: --dry");
+
+    let app_definition = AppDefBuilder::new("deploy".to_string())
+        .matching(Matching { ignore_case: true, ignore_separators: false })
+        .abbreviations(true)
+        .add_flag(
+                BooleanFlagDefBuilder::new("dry-run".to_string(), None, Some("dry-run".to_string()))
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .build()
+        .unwrap();
+
+    let app = app_definition.parse_args(&["--DRY"]).unwrap();
+    assert_eq!(app.boolean_flags.get("dry-run"), Some(&true));
+    assert_eq!(app.abbreviations.get("DRY"), Some(&"dry-run".to_string()));
+    let app = app_definition.parse_args(&["--dry_run"]);
+    assert_eq!(app.unwrap_err(), "unknown option: --dry_run, did you mean --dry-run?");
+}
+
+#[test]
+fn test_app_with_clashing_names() {
+    let app_definition = AppDefBuilder::new("deploy".to_string())
+        .matching(Matching { ignore_case: false, ignore_separators: true })
+        .add_flag(
+                BooleanFlagDefBuilder::new("dry-run".to_string(), None, Some("dry-run".to_string()))
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .add_option(
+                OptionDef::new_single_value("dry_run".to_string(), None, Some("dry_run".to_string()))
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .build();
+    assert_eq!(
+        app_definition.err(),
+        Some("long names --dry-run and --dry_run cannot be told apart".to_string())
+    );
+
+    let app_definition = AppDefBuilder::new("deploy".to_string())
+        .matching(Matching { ignore_case: true, ignore_separators: false })
+        .add_flag(
+                BooleanFlagDefBuilder::new("Verbose".to_string(), None, Some("Verbose".to_string()))
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .build();
+    assert!(app_definition.is_err());
+
+    let app_definition = AppDefBuilder::new("deploy".to_string())
+        .add_flag(
+                BooleanFlagDefBuilder::new("Verbose".to_string(), None, Some("Verbose".to_string()))
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .build();
+    assert!(app_definition.is_ok());
+
+    let app_definition = AppDefBuilder::new("deploy".to_string())
+        .add_flag(
+                BooleanFlagDefBuilder::new("dry-run".to_string(), None, Some("dry-run".to_string()))
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .add_option(
+                OptionDef::new_single_value("simulate".to_string(), None, Some("dry-run".to_string()))
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .build();
+    assert_eq!(
+        app_definition.err(),
+        Some("long names --dry-run and --dry-run cannot be told apart".to_string())
+    );
+}
+
+#[test]
+fn test_app_with_own_verbose_flag() {
+    let app_definition = AppDefBuilder::new("deploy".to_string())
+        .add_flag(
+                BooleanFlagDefBuilder::new("verbose".to_string(), Some('v'), Some("verbose".to_string()))
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .build()
+        .unwrap();
+    let app = app_definition.parse_args(&["-v"]).unwrap();
+    assert_eq!(app.boolean_flags.get("verbose"), Some(&true));
+    let app = app_definition.parse_args(&["-V"]);
+    assert_eq!(app.unwrap_err(), "unknown option: -V");
+
+    let app_definition = AppDefBuilder::new("deploy".to_string())
+        .add_option(
+                OptionDef::new_single_value("version".to_string(), Some('V'), Some("version".to_string()))
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .build()
+        .unwrap();
+    let app = app_definition.parse_args(&["-V", "2"]).unwrap();
+    assert_eq!(app.value_of("version"), Some("2"));
+    let app = app_definition.parse_args(&["--verbose"]);
+    assert!(app.is_err());
+}
+
+#[test]
+fn test_app_with_clashing_short_names() {
+    let app_definition = AppDefBuilder::new("deploy".to_string())
+        .add_flag(
+                BooleanFlagDefBuilder::new("force".to_string(), Some('f'), Some("force".to_string()))
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .add_option(
+                OptionDef::new_single_value("file".to_string(), Some('f'), Some("file".to_string()))
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .build();
+    assert_eq!(app_definition.err(), Some("short names -f and -f cannot be told apart".to_string()));
+
+    let app_definition = AppDefBuilder::new("deploy".to_string())
+        .matching(Matching { ignore_case: true, ignore_separators: false })
+        .add_flag(
+                BooleanFlagDefBuilder::new("force".to_string(), Some('f'), Some("force".to_string()))
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .add_option(
+                OptionDef::new_single_value("file".to_string(), Some('F'), Some("file".to_string()))
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .build();
+    assert!(app_definition.is_ok());
+}
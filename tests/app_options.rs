@@ -0,0 +1,46 @@
+use rust_yap::{AppDefBuilder, OptionDef};
+
+
+#[test]
+fn test_app_with_attached_option_values() {
+    let app_definition = AppDefBuilder::new("abc".to_string())
+        .add_option(
+                OptionDef::new_single_value("color".to_string(), Some('c'), Some("color".to_string()))
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .add_option(
+                OptionDef::new_multi_value("output".to_string(), Some('o'), Some("output".to_string()))
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .build()
+        .unwrap();
+
+    let app = app_definition.parse_args(&["--color=red"]).unwrap();
+    assert_eq!(app.single_value_options.get("color"), Some(&"red".into()));
+
+    let app = app_definition.parse_args(&["-cred"]).unwrap();
+    assert_eq!(app.single_value_options.get("color"), Some(&"red".into()));
+
+    let app = app_definition.parse_args(&["-c=red"]).unwrap();
+    assert_eq!(app.single_value_options.get("color"), Some(&"red".into()));
+
+    let app = app_definition.parse_args(&["-c", "red"]).unwrap();
+    assert_eq!(app.single_value_options.get("color"), Some(&"red".into()));
+
+    let app = app_definition.parse_args(&["-Vofile1", "--output=file2", "-o", "file3"]).unwrap();
+    assert_eq!(app.boolean_flags.get("verbose"), Some(&true));
+    assert_eq!(
+        app.multi_value_options.get("output"),
+        Some(&vec!["file1".into(), "file2".into(), "file3".into()])
+    );
+
+    let app = app_definition.parse_args(&["--verbose=yes"]);
+    assert!(app.is_err());
+
+    let app = app_definition.parse_args(&["--color=red", "--color=blue"]);
+    assert!(app.is_err());
+}
@@ -0,0 +1,81 @@
+use std::ffi::OsString;
+use std::path::Path;
+
+use rust_yap::{AppDefBuilder, ArgumentDef, OptionDef};
+
+
+#[test]
+fn test_app_with_os_string_arguments() {
+    let app_definition = AppDefBuilder::new("abc".to_string())
+        .add_option(
+                OptionDef::new_single_value("output".to_string(), Some('o'), Some("output".to_string()))
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .add_option(
+                OptionDef::new_multi_value("input".to_string(), Some('i'), Some("input".to_string()))
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .add_argument(
+                ArgumentDef::new_single_value("file".to_string())
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .build()
+        .unwrap();
+
+    let args: Vec<OsString> = vec!["-o".into(), "out.txt".into(), "-ia.txt".into(), "b.txt".into()];
+    let app = app_definition.parse_args_os(&args).unwrap();
+    assert_eq!(app.value_of("output"), Some("out.txt"));
+    assert_eq!(app.path_of("output"), Some(Path::new("out.txt")));
+    assert_eq!(app.values_of("input"), Some(vec!["a.txt"]));
+    assert_eq!(app.value_of("file"), Some("b.txt"));
+    assert_eq!(app.value_of("missing"), None);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_app_with_non_utf8_arguments() {
+    use std::os::unix::ffi::OsStringExt;
+
+    let app_definition = AppDefBuilder::new("abc".to_string())
+        .add_option(
+                OptionDef::new_single_value("output".to_string(), Some('o'), Some("output".to_string()))
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .add_option(
+                OptionDef::new_multi_value("input".to_string(), Some('i'), Some("input".to_string()))
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .add_argument(
+                ArgumentDef::new_single_value("file".to_string())
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .build()
+        .unwrap();
+
+    let name = OsString::from_vec(b"caf\xe9.txt".to_vec());
+    let mut attached = OsString::from("--output=");
+    attached.push(&name);
+    let args = vec![attached, "-i".into(), "a.txt".into(), "-i".into(), name.clone(), name.clone()];
+    let app = app_definition.parse_args_os(&args).unwrap();
+    assert_eq!(app.value_of("output"), None);
+    assert_eq!(app.value_of_os("output"), Some(name.as_os_str()));
+    assert_eq!(app.path_of("output"), Some(Path::new(&name)));
+    assert_eq!(app.values_of("input"), None);
+    assert_eq!(
+        app.values_of_os("input"),
+        Some(&["a.txt".into(), name.clone()][..])
+    );
+    assert_eq!(app.path_of("file"), Some(Path::new(&name)));
+}
@@ -0,0 +1,49 @@
+use rust_yap::{AppDefBuilder, ArgumentDef, BooleanFlagDefBuilder, OptionDef};
+
+
+#[test]
+fn test_app_parse_str() {
+    let app_definition = AppDefBuilder::new("job".to_string())
+        .add_flag(
+                BooleanFlagDefBuilder::new("verbose".to_string(), Some('v'), Some("verbose".to_string()))
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .add_option(
+                OptionDef::new_single_value("opt".to_string(), None, Some("opt".to_string()))
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .add_argument(
+                ArgumentDef::new_single_value("command".to_string())
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .add_argument(
+                ArgumentDef::new_single_value("target".to_string())
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .build()
+        .unwrap();
+
+    let app = app_definition.parse_str("--opt 'a b' cmd \"c\\\"d\"").unwrap();
+    assert_eq!(app.value_of("command"), Some("cmd"));
+    assert_eq!(app.value_of("opt"), Some("a b"));
+    assert_eq!(app.value_of("target"), Some("c\"d"));
+
+    let app = app_definition.parse_str("  -v\t--opt=x\\ y   cmd ").unwrap();
+    assert_eq!(app.boolean_flags.get("verbose"), Some(&true));
+    assert_eq!(app.value_of("opt"), Some("x y"));
+
+    let app = app_definition.parse_str("--opt 'a b");
+    assert_eq!(app.unwrap_err(), "unterminated single quote at character 7");
+    let app = app_definition.parse_str("cmd \"x");
+    assert_eq!(app.unwrap_err(), "unterminated double quote at character 5");
+    let app = app_definition.parse_str("cmd x\\");
+    assert_eq!(app.unwrap_err(), "unterminated escape at character 6");
+}
@@ -0,0 +1,299 @@
+use rust_yap::{AppDefBuilder, ArgumentDef, BasicSubCommandDefBuilder, Matching, OptionDef, PossibleValue};
+
+
+#[test]
+fn test_app_with_possible_values() {
+    let app_definition = AppDefBuilder::new("copy".to_string())
+        .add_option(
+                OptionDef::new_single_value("speed".to_string(), Some('s'), Some("speed".to_string()))
+                    .help(Some("transfer speed".to_string()))
+                    .possible_values(vec![
+                        PossibleValue::new("fast".to_string(), Some("use all bandwidth".to_string())),
+                        PossibleValue::new("slow".to_string(), None),
+                    ])
+                    .default_value(Some("fast".to_string()))
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .add_option(
+                OptionDef::new_multi_value("hash".to_string(), None, Some("hash".to_string()))
+                    .possible_values(vec![
+                        PossibleValue::new("md5".to_string(), None),
+                        PossibleValue::new("sha256".to_string(), None),
+                    ])
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .add_argument(
+                ArgumentDef::new_single_value("mode".to_string())
+                    .help(Some("what to copy".to_string()))
+                    .possible_values(vec![
+                        PossibleValue::new("all".to_string(), None),
+                        PossibleValue::new("new".to_string(), Some("only files missing at the target".to_string())),
+                    ])
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .subcommand(Some(Box::new(
+                BasicSubCommandDefBuilder::new("verify".to_string())
+                    .add_argument(
+                            ArgumentDef::new_multi_value("level".to_string())
+                                .possible_values(vec![
+                                    PossibleValue::new("quick".to_string(), None),
+                                    PossibleValue::new("full".to_string(), None),
+                                ])
+                                .build()
+                                .unwrap()
+                                .into()
+                    )
+                    .build()
+                    .unwrap()
+                    .into()
+        )))
+        .build()
+        .unwrap();
+
+    let app = app_definition.parse_args(&["-s", "slow", "--hash", "md5", "--hash", "sha256", "new"]).unwrap();
+    assert_eq!(app.value_of("speed"), Some("slow"));
+    assert_eq!(app.values_of("hash"), Some(vec!["md5", "sha256"]));
+    assert_eq!(app.value_of("mode"), Some("new"));
+
+    let app = app_definition.parse_args(&["all", "verify", "full", "quick"]).unwrap();
+    assert_eq!(app.value_of("speed"), Some("fast"));
+    assert_eq!(app.subcommand().unwrap().1.values_of("level"), Some(vec!["full", "quick"]));
+
+    let app = app_definition.parse_args(&["-s", "medium", "all"]);
+    assert_eq!(app.unwrap_err(), "option speed expects one of fast, slow, got: medium");
+    let app = app_definition.parse_args(&["--hash", "sha1", "all"]);
+    assert_eq!(app.unwrap_err(), "option hash expects one of md5, sha256, got: sha1");
+    let app = app_definition.parse_args(&["some"]);
+    assert_eq!(app.unwrap_err(), "argument mode expects one of all, new, got: some");
+    let app = app_definition.parse_args(&["all", "verify", "Full"]);
+    assert_eq!(app.unwrap_err(), "argument level expects one of quick, full, got: Full, did you mean full?");
+}
+
+#[test]
+fn test_app_with_case_insensitive_possible_values() {
+    let app_definition = AppDefBuilder::new("copy".to_string())
+        .matching(Matching { ignore_case: true, ignore_separators: false })
+        .add_option(
+                OptionDef::new_single_value("speed".to_string(), Some('s'), Some("speed".to_string()))
+                    .help(Some("transfer speed".to_string()))
+                    .possible_values(vec![
+                        PossibleValue::new("fast".to_string(), Some("use all bandwidth".to_string())),
+                        PossibleValue::new("slow".to_string(), None),
+                    ])
+                    .default_value(Some("fast".to_string()))
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .add_option(
+                OptionDef::new_multi_value("hash".to_string(), None, Some("hash".to_string()))
+                    .possible_values(vec![
+                        PossibleValue::new("md5".to_string(), None),
+                        PossibleValue::new("sha256".to_string(), None),
+                    ])
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .add_argument(
+                ArgumentDef::new_single_value("mode".to_string())
+                    .help(Some("what to copy".to_string()))
+                    .possible_values(vec![
+                        PossibleValue::new("all".to_string(), None),
+                        PossibleValue::new("new".to_string(), Some("only files missing at the target".to_string())),
+                    ])
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .subcommand(Some(Box::new(
+                BasicSubCommandDefBuilder::new("verify".to_string())
+                    .add_argument(
+                            ArgumentDef::new_multi_value("level".to_string())
+                                .possible_values(vec![
+                                    PossibleValue::new("quick".to_string(), None),
+                                    PossibleValue::new("full".to_string(), None),
+                                ])
+                                .build()
+                                .unwrap()
+                                .into()
+                    )
+                    .build()
+                    .unwrap()
+                    .into()
+        )))
+        .build()
+        .unwrap();
+
+    let app = app_definition.parse_args(&["-s", "SLOW", "--hash", "Md5", "All", "verify", "Full"]).unwrap();
+    assert_eq!(app.value_of("speed"), Some("slow"));
+    assert_eq!(app.values_of("hash"), Some(vec!["md5"]));
+    assert_eq!(app.value_of("mode"), Some("all"));
+    assert_eq!(app.subcommand().unwrap().1.values_of("level"), Some(vec!["full"]));
+}
+
+#[test]
+fn test_usage_with_possible_values() {
+    let app_definition = AppDefBuilder::new("copy".to_string())
+        .add_option(
+                OptionDef::new_single_value("speed".to_string(), Some('s'), Some("speed".to_string()))
+                    .help(Some("transfer speed".to_string()))
+                    .possible_values(vec![
+                        PossibleValue::new("fast".to_string(), Some("use all bandwidth".to_string())),
+                        PossibleValue::new("slow".to_string(), None),
+                    ])
+                    .default_value(Some("fast".to_string()))
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .add_option(
+                OptionDef::new_multi_value("hash".to_string(), None, Some("hash".to_string()))
+                    .possible_values(vec![
+                        PossibleValue::new("md5".to_string(), None),
+                        PossibleValue::new("sha256".to_string(), None),
+                    ])
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .add_argument(
+                ArgumentDef::new_single_value("mode".to_string())
+                    .help(Some("what to copy".to_string()))
+                    .possible_values(vec![
+                        PossibleValue::new("all".to_string(), None),
+                        PossibleValue::new("new".to_string(), Some("only files missing at the target".to_string())),
+                    ])
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .subcommand(Some(Box::new(
+                BasicSubCommandDefBuilder::new("verify".to_string())
+                    .add_argument(
+                            ArgumentDef::new_multi_value("level".to_string())
+                                .possible_values(vec![
+                                    PossibleValue::new("quick".to_string(), None),
+                                    PossibleValue::new("full".to_string(), None),
+                                ])
+                                .build()
+                                .unwrap()
+                                .into()
+                    )
+                    .build()
+                    .unwrap()
+                    .into()
+        )))
+        .build()
+        .unwrap();
+
+    let usage = app_definition.get_subcommand_usage(&[]).unwrap();
+    assert!(usage.contains("    -s, --speed {fast|slow} transfer speed [default: fast]\n        fast: use all bandwidth\n    --hash"));
+    assert!(usage.contains("    --hash [{md5|sha256}] [...]\n"));
+    assert!(usage.contains("    mode {all|new} what to copy\n        new: only files missing at the target\n"));
+    let usage = app_definition.get_subcommand_usage(&["verify"]).unwrap();
+    assert!(usage.contains("    level {quick|full}\n"));
+
+    let names = |values: &[PossibleValue]| values.iter().map(|pv| pv.name.clone()).collect::<Vec<_>>();
+    assert_eq!(names(app_definition.possible_values(&[], "speed").unwrap()), vec!["fast", "slow"]);
+    assert_eq!(names(app_definition.possible_values(&[], "mode").unwrap()), vec!["all", "new"]);
+    assert_eq!(names(app_definition.possible_values(&["verify"], "level").unwrap()), vec!["quick", "full"]);
+    assert_eq!(app_definition.possible_values(&[], "verbose"), None);
+    assert_eq!(app_definition.possible_values(&["verify"], "speed"), None);
+}
+
+#[test]
+fn test_app_with_possible_values_ignoring_case() {
+    let app_definition = AppDefBuilder::new("copy".to_string())
+        .add_option(
+                OptionDef::new_single_value("speed".to_string(), Some('s'), Some("speed".to_string()))
+                    .possible_values(vec![
+                        PossibleValue::new("fast".to_string(), None),
+                        PossibleValue::new("slow".to_string(), None),
+                    ])
+                    .ignore_case(true)
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .add_argument(
+                ArgumentDef::new_multi_value("mode".to_string())
+                    .possible_values(vec![
+                        PossibleValue::new("all".to_string(), None),
+                        PossibleValue::new("new".to_string(), None),
+                    ])
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .build()
+        .unwrap();
+
+    let app = app_definition.parse_args(&["--speed", "SLOW", "all"]).unwrap();
+    assert_eq!(app.value_of("speed"), Some("slow"));
+    assert_eq!(app.values_of("mode"), Some(vec!["all"]));
+
+    let app = app_definition.parse_args(&["--Speed", "slow"]);
+    assert_eq!(app.unwrap_err(), "unknown option: --Speed, did you mean --speed?");
+    let app = app_definition.parse_args(&["-s", "fast", "All"]);
+    assert_eq!(app.unwrap_err(), "argument mode expects one of all, new, got: All, did you mean all?");
+}
+
+#[test]
+fn test_possible_values_that_cannot_be_told_apart() {
+    let app_definition = AppDefBuilder::new("copy".to_string())
+        .add_option(
+                OptionDef::new_single_value("speed".to_string(), Some('s'), Some("speed".to_string()))
+                    .possible_values(vec![
+                        PossibleValue::new("fast".to_string(), None),
+                        PossibleValue::new("Fast".to_string(), None),
+                    ])
+                    .ignore_case(true)
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .build();
+    assert_eq!(
+        app_definition.err(),
+        Some("possible values fast and Fast of option speed cannot be told apart".to_string())
+    );
+
+    let app_definition = AppDefBuilder::new("copy".to_string())
+        .matching(Matching { ignore_case: false, ignore_separators: true })
+        .add_argument(
+                ArgumentDef::new_single_value("mode".to_string())
+                    .possible_values(vec![
+                        PossibleValue::new("new-only".to_string(), None),
+                        PossibleValue::new("new_only".to_string(), None),
+                    ])
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .build();
+    assert_eq!(
+        app_definition.err(),
+        Some("possible values new-only and new_only of argument mode cannot be told apart".to_string())
+    );
+
+    let app_definition = AppDefBuilder::new("copy".to_string())
+        .add_option(
+                OptionDef::new_single_value("speed".to_string(), Some('s'), Some("speed".to_string()))
+                    .possible_values(vec![
+                        PossibleValue::new("fast".to_string(), None),
+                        PossibleValue::new("Fast".to_string(), None),
+                    ])
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .build();
+    assert!(app_definition.is_ok());
+}
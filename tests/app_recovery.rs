@@ -0,0 +1,83 @@
+use rust_yap::{AppDefBuilder, ArgumentDef, BooleanFlagDefBuilder, OptionDef};
+
+
+#[test]
+fn test_app_recovery() {
+    let app_definition = AppDefBuilder::new("copy".to_string())
+        .add_flag(
+                BooleanFlagDefBuilder::new("force".to_string(), Some('f'), Some("force".to_string()))
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .add_option(
+                OptionDef::new_single_value("mode".to_string(), Some('m'), Some("mode".to_string()))
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .add_option(
+                OptionDef::new_single_value("owner".to_string(), Some('o'), Some("owner".to_string()))
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .add_argument(
+                ArgumentDef::new_single_value("source".to_string())
+                    .required(true)
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .build()
+        .unwrap();
+
+    let (app, errors) = app_definition.parse_args_recover(&["-f"]);
+    assert_eq!(app.boolean_flags.get("force"), Some(&true));
+    assert_eq!(
+        errors,
+        vec!["required positional argument: source is missing".to_string()]
+    );
+
+    let (app, errors) = app_definition.parse_args_recover(
+        &["-x", "--mode", "--force", "--force=yes", "-f-", "--owner", "me", "--=", "a", "b"]
+    );
+    assert_eq!(
+        errors,
+        vec![
+            "unknown option: -x".to_string(),
+            "option mode requires a value".to_string(),
+            "flag force does not take a value".to_string(),
+            "flag force may appear only once".to_string(),
+            "invalid character (argument 5, character 3)".to_string(),
+            "invalid character (argument 8, character 3)".to_string(),
+            "unexpected argument: b".to_string(),
+        ]
+    );
+    assert_eq!(app.boolean_flags.get("force"), Some(&true));
+    assert_eq!(app.value_of("owner"), Some("me"));
+    assert_eq!(app.value_of("source"), Some("a"));
+
+    let (app, errors) = app_definition.parse_args_recover(&["--colr=1", "-f=yes", "-x=1", "a"]);
+    assert_eq!(
+        errors,
+        vec![
+            "unknown option: --colr".to_string(),
+            "flag force does not take a value".to_string(),
+            "unknown option: -x".to_string(),
+        ]
+    );
+    assert_eq!(app.boolean_flags.get("force"), None);
+    assert_eq!(app.value_of("source"), Some("a"));
+
+    let (app, errors) = app_definition.parse_args_recover(&["-m", "644", "a"]);
+    assert!(errors.is_empty());
+    assert_eq!(app.value_of("mode"), Some("644"));
+
+    let app = app_definition.parse_args(&["-x", "--mode"]);
+    assert_eq!(app.unwrap_err(), "unknown option: -x");
+    let app = app_definition.parse_args(&["a", "--mode"]);
+    assert!(app.is_err());
+    let app = app_definition.parse_args(&["--mode"]);
+    assert_eq!(app.unwrap_err(), "option mode requires a value");
+}
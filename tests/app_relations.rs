@@ -0,0 +1,254 @@
+use rust_yap::{AppDefBuilder, ArgumentDef, BooleanFlagDefBuilder, OptionDef, RelationsBuilder, ValueSource};
+
+
+#[test]
+fn test_app_with_relations() {
+    let app_definition = AppDefBuilder::new("export".to_string())
+        .add_flag(
+                BooleanFlagDefBuilder::new("quiet".to_string(), Some('q'), Some("quiet".to_string()))
+                    .relations(RelationsBuilder::new().conflicts_with(vec!["verbose".to_string()]).build().unwrap())
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .add_flag(
+                BooleanFlagDefBuilder::new("json".to_string(), None, Some("json".to_string()))
+                    .relations(RelationsBuilder::new().overrides_with(vec!["plain".to_string()]).build().unwrap())
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .add_flag(
+                BooleanFlagDefBuilder::new("plain".to_string(), None, Some("plain".to_string()))
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .add_flag(
+                BooleanFlagDefBuilder::new("anonymous".to_string(), Some('a'), Some("anonymous".to_string()))
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .add_option(
+                OptionDef::new_single_value("token".to_string(), Some('t'), Some("token".to_string()))
+                    .relations(RelationsBuilder::new().required_unless_present(vec!["anonymous".to_string()]).build().unwrap())
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .add_option(
+                OptionDef::new_single_value("auth".to_string(), None, Some("auth".to_string()))
+                    .default_value(Some("basic".to_string()))
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .add_option(
+                OptionDef::new_single_value("password".to_string(), None, Some("password".to_string()))
+                    .relations(RelationsBuilder::new().required_if_eq("auth".to_string(), "basic".to_string()).build().unwrap())
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .add_option(
+                OptionDef::new_single_value("compress".to_string(), None, Some("compress".to_string()))
+                    .relations(RelationsBuilder::new().requires(vec!["output".to_string()]).conflicts_with(vec!["plain".to_string()]).build().unwrap())
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .add_argument(
+                ArgumentDef::new_single_value("output".to_string())
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .build()
+        .unwrap();
+
+    let app = app_definition.parse_args(&["-a", "-q", "--compress", "gz", "out.gz"]).unwrap();
+    assert_eq!(app.boolean_flags.get("quiet"), Some(&true));
+    assert_eq!(app.value_of("compress"), Some("gz"));
+    // a default does not make the password required
+    assert_eq!(app.source_of("auth"), Some(ValueSource::Default));
+    let app = app_definition.parse_args(&["-t", "secret", "--auth", "token"]).unwrap();
+    assert_eq!(app.value_of("token"), Some("secret"));
+
+    let app = app_definition.parse_args(&["-a", "-q", "-V"]);
+    assert_eq!(app.unwrap_err(), "flag quiet cannot be used with flag verbose");
+    let app = app_definition.parse_args(&["-a", "--plain", "--compress", "gz", "out.gz"]);
+    assert_eq!(app.unwrap_err(), "option compress cannot be used with flag plain");
+    let app = app_definition.parse_args(&["-a", "--compress", "gz"]);
+    assert_eq!(app.unwrap_err(), "option compress requires argument output");
+    let app = app_definition.parse_args(&[]);
+    assert_eq!(app.unwrap_err(), "option token is required unless flag anonymous is given");
+    let app = app_definition.parse_args(&["-a", "--auth", "basic"]);
+    assert_eq!(app.unwrap_err(), "option password is required when option auth is basic");
+    let app = app_definition.parse_args(&["-a", "--auth", "basic", "--password", "x"]).unwrap();
+    assert_eq!(app.value_of("password"), Some("x"));
+}
+
+#[test]
+fn test_app_with_overrides() {
+    let app_definition = AppDefBuilder::new("export".to_string())
+        .add_flag(
+                BooleanFlagDefBuilder::new("quiet".to_string(), Some('q'), Some("quiet".to_string()))
+                    .relations(RelationsBuilder::new().conflicts_with(vec!["verbose".to_string()]).build().unwrap())
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .add_flag(
+                BooleanFlagDefBuilder::new("json".to_string(), None, Some("json".to_string()))
+                    .relations(RelationsBuilder::new().overrides_with(vec!["plain".to_string()]).build().unwrap())
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .add_flag(
+                BooleanFlagDefBuilder::new("plain".to_string(), None, Some("plain".to_string()))
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .add_flag(
+                BooleanFlagDefBuilder::new("anonymous".to_string(), Some('a'), Some("anonymous".to_string()))
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .add_option(
+                OptionDef::new_single_value("token".to_string(), Some('t'), Some("token".to_string()))
+                    .relations(RelationsBuilder::new().required_unless_present(vec!["anonymous".to_string()]).build().unwrap())
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .add_option(
+                OptionDef::new_single_value("auth".to_string(), None, Some("auth".to_string()))
+                    .default_value(Some("basic".to_string()))
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .add_option(
+                OptionDef::new_single_value("password".to_string(), None, Some("password".to_string()))
+                    .relations(RelationsBuilder::new().required_if_eq("auth".to_string(), "basic".to_string()).build().unwrap())
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .add_option(
+                OptionDef::new_single_value("compress".to_string(), None, Some("compress".to_string()))
+                    .relations(RelationsBuilder::new().requires(vec!["output".to_string()]).conflicts_with(vec!["plain".to_string()]).build().unwrap())
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .add_argument(
+                ArgumentDef::new_single_value("output".to_string())
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .build()
+        .unwrap();
+
+    let app = app_definition.parse_args(&["-a", "--plain", "--json"]).unwrap();
+    assert_eq!(app.boolean_flags.get("json"), Some(&true));
+    assert_eq!(app.boolean_flags.get("plain"), None);
+    assert!(!app.is_present("plain"));
+
+    let app = app_definition.parse_args(&["--json", "-a", "--plain"]).unwrap();
+    assert_eq!(app.boolean_flags.get("json"), None);
+    assert_eq!(app.boolean_flags.get("plain"), Some(&true));
+}
+
+#[test]
+fn test_app_with_unknown_relation() {
+    let app_definition = AppDefBuilder::new("export".to_string())
+        .add_flag(
+                BooleanFlagDefBuilder::new("quiet".to_string(), Some('q'), Some("quiet".to_string()))
+                    .relations(RelationsBuilder::new().conflicts_with(vec!["verbos".to_string()]).build().unwrap())
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .build();
+    assert_eq!(app_definition.err(), Some("flag quiet refers to unknown flag, option or argument verbos".to_string()));
+}
+
+#[test]
+fn test_usage_with_relations() {
+    let app_definition = AppDefBuilder::new("export".to_string())
+        .add_flag(
+                BooleanFlagDefBuilder::new("quiet".to_string(), Some('q'), Some("quiet".to_string()))
+                    .relations(RelationsBuilder::new().conflicts_with(vec!["verbose".to_string()]).build().unwrap())
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .add_flag(
+                BooleanFlagDefBuilder::new("json".to_string(), None, Some("json".to_string()))
+                    .relations(RelationsBuilder::new().overrides_with(vec!["plain".to_string()]).build().unwrap())
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .add_flag(
+                BooleanFlagDefBuilder::new("plain".to_string(), None, Some("plain".to_string()))
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .add_flag(
+                BooleanFlagDefBuilder::new("anonymous".to_string(), Some('a'), Some("anonymous".to_string()))
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .add_option(
+                OptionDef::new_single_value("token".to_string(), Some('t'), Some("token".to_string()))
+                    .relations(RelationsBuilder::new().required_unless_present(vec!["anonymous".to_string()]).build().unwrap())
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .add_option(
+                OptionDef::new_single_value("auth".to_string(), None, Some("auth".to_string()))
+                    .default_value(Some("basic".to_string()))
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .add_option(
+                OptionDef::new_single_value("password".to_string(), None, Some("password".to_string()))
+                    .relations(RelationsBuilder::new().required_if_eq("auth".to_string(), "basic".to_string()).build().unwrap())
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .add_option(
+                OptionDef::new_single_value("compress".to_string(), None, Some("compress".to_string()))
+                    .relations(RelationsBuilder::new().requires(vec!["output".to_string()]).conflicts_with(vec!["plain".to_string()]).build().unwrap())
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .add_argument(
+                ArgumentDef::new_single_value("output".to_string())
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .build()
+        .unwrap();
+
+    let usage = app_definition.get_subcommand_usage(&[]).unwrap();
+    assert!(usage.contains("    -q, --quiet [conflicts with: verbose]\n"));
+    assert!(usage.contains("    --json [overrides: plain]\n"));
+    assert!(usage.contains("    -t, --token [required unless: anonymous]\n"));
+    assert!(usage.contains("    --password [required if: auth=basic]\n"));
+    assert!(usage.contains("    --compress [conflicts with: plain] [requires: output]\n"));
+}
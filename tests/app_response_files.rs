@@ -0,0 +1,75 @@
+use std::fs;
+use std::path::PathBuf;
+
+use rust_yap::parser::Position;
+use rust_yap::response_file;
+use rust_yap::{AppDefBuilder, OptionDef};
+
+
+fn test_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("rust_yap_{}", name));
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+#[test]
+fn test_response_file_expansion() {
+    let dir = test_dir("response_file_expansion");
+    fs::write(dir.join("args.txt"), "--color 'dark red'\n# comment\n@nested.txt @@literal\n").unwrap();
+    fs::write(dir.join("nested.txt"), "-V \"a \\\"b\\\"\"").unwrap();
+    let args_file = format!("@{}", dir.join("args.txt").display());
+
+    let expanded = response_file::expand(&["first", &args_file, "@@last", "@"]).unwrap();
+    assert_eq!(
+        expanded,
+        vec!["first", "--color", "dark red", "-V", "a \"b\"", "@literal", "@last", "@"]
+    );
+
+    let expanded = response_file::expand(&["@@first", "--", &args_file, "@@last"]).unwrap();
+    assert_eq!(expanded, vec!["@first", "--", args_file.as_str(), "@@last"]);
+    fs::write(dir.join("separated.txt"), "a -- @nested.txt").unwrap();
+    let separated_file = format!("@{}", dir.join("separated.txt").display());
+    let expanded = response_file::expand(&[&separated_file, &args_file]).unwrap();
+    assert_eq!(expanded, vec!["a", "--", "@nested.txt", args_file.as_str()]);
+
+    let app_definition = AppDefBuilder::new("abc".to_string())
+        .add_option(
+                OptionDef::new_single_value("color".to_string(), Some('c'), Some("color".to_string()))
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .response_files(true)
+        .build()
+        .unwrap();
+    fs::write(dir.join("app.txt"), "--color 'dark red' -V").unwrap();
+    let app_file = format!("@{}", dir.join("app.txt").display());
+    let app = app_definition.parse_args(&[&app_file]).unwrap();
+    assert_eq!(app.value_of("color"), Some("dark red"));
+    assert_eq!(app.boolean_flags.get("verbose"), Some(&true));
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_response_file_errors() {
+    let dir = test_dir("response_file_errors");
+    fs::write(dir.join("a.txt"), "x @b.txt").unwrap();
+    fs::write(dir.join("b.txt"), "y @a.txt").unwrap();
+    fs::write(dir.join("quote.txt"), "x\n'y").unwrap();
+    let a_file = format!("@{}", dir.join("a.txt").display());
+    let quote_file = format!("@{}", dir.join("quote.txt").display());
+    let missing_file = format!("@{}", dir.join("missing.txt").display());
+
+    let err = response_file::expand(&["first", &a_file]).unwrap_err();
+    assert_eq!(err.position, Position::new(1));
+    assert!(err.message.contains("includes itself"));
+
+    let err = response_file::expand(&[&quote_file]).unwrap_err();
+    assert_eq!(err.position, Position::new(0));
+    assert!(err.message.ends_with("quote.txt:2: unterminated single quote"));
+
+    let err = response_file::expand(&["a", "b", &missing_file]).unwrap_err();
+    assert_eq!(err.position, Position::new(2));
+    assert!(err.to_string().ends_with("(argument 3)"));
+    fs::remove_dir_all(dir).unwrap();
+}
@@ -0,0 +1,90 @@
+use std::ffi::OsString;
+
+use rust_yap::{AppDefBuilder, ArgumentDef, BasicSubCommandDefBuilder, BooleanFlagDefBuilder};
+
+
+#[test]
+fn test_app_with_positional_separator() {
+    let app_definition = AppDefBuilder::new("rm".to_string())
+        .add_flag(
+                BooleanFlagDefBuilder::new("force".to_string(), Some('f'), Some("force".to_string()))
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .add_argument(
+                ArgumentDef::new_multi_value("files".to_string())
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .build()
+        .unwrap();
+
+    let app = app_definition.parse_args(&["-f", "--", "-x", "--force", "--"]).unwrap();
+    assert_eq!(app.boolean_flags.get("force"), Some(&true));
+    assert_eq!(app.values_of("files"), Some(vec!["-x", "--force", "--"]));
+    assert!(app.trailing().is_empty());
+
+    let app = app_definition.parse_args(&["a", "--", "-f"]).unwrap();
+    assert_eq!(app.boolean_flags.get("force"), None);
+    assert_eq!(app.values_of("files"), Some(vec!["a", "-f"]));
+
+    let app = app_definition.parse_args(&["--"]);
+    assert_eq!(app.unwrap_err(), "positional argument files requires at least 1 value(s), 0 given");
+}
+
+#[test]
+fn test_app_with_trailing_arguments() {
+    let app_definition = AppDefBuilder::new("wrap".to_string())
+        .add_argument(
+                ArgumentDef::new_single_value("program".to_string())
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .trailing(true)
+        .build()
+        .unwrap();
+
+    let app = app_definition.parse_args(&["env", "--", "ls", "-l", "--", "/tmp"]).unwrap();
+    assert_eq!(app.value_of("program"), Some("env"));
+    assert_eq!(
+        app.trailing(),
+        &[OsString::from("ls"), OsString::from("-l"), OsString::from("--"), OsString::from("/tmp")]
+    );
+
+    let app = app_definition.parse_args(&["--", "ls"]).unwrap();
+    assert_eq!(app.value_of("program"), None);
+    assert_eq!(app.trailing(), &[OsString::from("ls")]);
+}
+
+#[test]
+fn test_subcommand_with_trailing_arguments() {
+    let app_definition = AppDefBuilder::new("cargo".to_string())
+        .subcommand(Some(Box::new(
+                BasicSubCommandDefBuilder::new("run".to_string())
+                    .add_flag(
+                            BooleanFlagDefBuilder::new("release".to_string(), None, Some("release".to_string()))
+                                .build()
+                                .unwrap()
+                                .into()
+                    )
+                    .trailing(true)
+                    .build()
+                    .unwrap()
+                    .into()
+        )))
+        .build()
+        .unwrap();
+
+    let app = app_definition.parse_args(&["run", "--release", "--", "--release", "run"]).unwrap();
+    let (name, run) = app.subcommand().unwrap();
+    assert_eq!(name, "run");
+    assert_eq!(run.boolean_flags.get("release"), Some(&true));
+    assert_eq!(run.trailing(), &[OsString::from("--release"), OsString::from("run")]);
+    assert!(app.trailing().is_empty());
+
+    let app = app_definition.parse_args(&["--", "run"]);
+    assert_eq!(app.unwrap_err(), "unexpected argument: run");
+}
@@ -0,0 +1,102 @@
+use std::io::{Read, Write};
+
+use rust_yap::{AppDefBuilder, ArgumentDef, OptionDef};
+
+
+#[test]
+fn test_app_with_stdio_values() {
+    let app_definition = AppDefBuilder::new("abc".to_string())
+        .add_option(
+                OptionDef::new_single_value("output".to_string(), Some('o'), Some("output".to_string()))
+                    .stdio(true)
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .add_argument(
+                ArgumentDef::new_single_value("input".to_string())
+                    .stdio(true)
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .build()
+        .unwrap();
+
+    let app = app_definition.parse_args(&["-o", "-", "-"]).unwrap();
+    assert_eq!(app.value_of("output"), Some("-"));
+    assert_eq!(app.value_of("input"), Some("-"));
+    assert!(app.is_stdio("output"));
+    assert!(app.is_stdio("input"));
+
+    let path = std::env::temp_dir().join("rust_yap_test_app_with_stdio_values.txt");
+    let path = path.to_str().unwrap();
+    let app = app_definition.parse_args(&["--output", path, path]).unwrap();
+    assert!(!app.is_stdio("output"));
+    assert!(!app.is_stdio("input"));
+
+    app.writer_of("output").unwrap().unwrap().write_all(b"data").unwrap();
+    let mut content = String::new();
+    app.reader_of("input").unwrap().unwrap().read_to_string(&mut content).unwrap();
+    assert_eq!(content, "data");
+    assert!(app.reader_of("missing").is_none());
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn test_app_with_dash_value_without_stdio() {
+    let app_definition = AppDefBuilder::new("abc".to_string())
+        .add_argument(
+                ArgumentDef::new_single_value("input".to_string())
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .build()
+        .unwrap();
+
+    let app = app_definition.parse_args(&["-"]).unwrap();
+    assert_eq!(app.value_of("input"), Some("-"));
+    assert!(!app.is_stdio("input"));
+}
+
+#[test]
+fn test_app_with_stdio_defaults() {
+    let app_definition = AppDefBuilder::new("abc".to_string())
+        .add_option(
+                OptionDef::new_single_value("output".to_string(), Some('o'), Some("output".to_string()))
+                    .stdio(true)
+                    .default_value(Some("-".to_string()))
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .add_argument(
+                ArgumentDef::new_single_value("input".to_string())
+                    .stdio(true)
+                    .default_value(Some("-".to_string()))
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .add_argument(
+                ArgumentDef::new_single_value("log".to_string())
+                    .default_value(Some("-".to_string()))
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .build()
+        .unwrap();
+
+    let app = app_definition.parse_args(&[]).unwrap();
+    assert_eq!(app.value_of("output"), Some("-"));
+    assert_eq!(app.value_of("input"), Some("-"));
+    assert!(app.is_stdio("output"));
+    assert!(app.is_stdio("input"));
+    assert!(!app.is_stdio("log"));
+
+    let app = app_definition.parse_args(&["-o", "out.txt", "in.txt"]).unwrap();
+    assert!(!app.is_stdio("output"));
+    assert!(!app.is_stdio("input"));
+}
@@ -0,0 +1,139 @@
+use rust_yap::{AppDefBuilder, ArgumentDef, BooleanFlagDefBuilder, OptionDef};
+
+
+#[test]
+fn test_app_with_streamed_argument() {
+    let app_definition = AppDefBuilder::new("xargs".to_string())
+        .add_option(
+                OptionDef::new_single_value("output".to_string(), Some('o'), Some("output".to_string()))
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .add_argument(
+                ArgumentDef::new_single_value("command".to_string())
+                    .required(true)
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .add_argument(
+                ArgumentDef::new_multi_value("files".to_string())
+                    .stream(true)
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .build()
+        .unwrap();
+
+    let args = vec!["-o".to_string(), "out".to_string(), "rm".to_string()]
+        .into_iter()
+        .chain((0..100_000).map(|i| format!("file{}", i)));
+    let (app, files) = app_definition.parse_args_iter(args).unwrap();
+    assert_eq!(app.value_of("output"), Some("out"));
+    assert_eq!(app.value_of("command"), Some("rm"));
+    assert_eq!(app.values_of("files"), None);
+    assert_eq!(files.name(), Some("files"));
+    let mut cnt = 0;
+    for (idx, file) in files.enumerate() {
+        assert_eq!(file.unwrap(), format!("file{}", idx).as_str());
+        cnt += 1;
+    }
+    assert_eq!(cnt, 100_000);
+
+    let (_, files) = app_definition.parse_args_iter(vec!["rm", "a", "--", "-b"]).unwrap();
+    assert_eq!(files.collect::<Result<Vec<_>, _>>().unwrap(), vec!["a", "-b"]);
+
+    let (_, files) = app_definition.parse_args_iter(vec!["rm", "a", "-b"]).unwrap();
+    assert_eq!(
+        files.collect::<Result<Vec<_>, _>>().unwrap_err(),
+        "option -b cannot follow the streamed values of files"
+    );
+
+    let app = app_definition.parse_args_iter(vec!["rm"]);
+    assert!(app.is_err());
+
+    let app = app_definition.parse_args_iter(Vec::<String>::new());
+    assert!(app.is_err());
+
+    let app = app_definition.parse_args(&["rm", "a", "b"]).unwrap();
+    assert_eq!(app.values_of("files"), Some(vec!["a", "b"]));
+}
+
+#[test]
+fn test_app_with_multi_value_argument() {
+    let app_definition = AppDefBuilder::new("cp".to_string())
+        .add_argument(
+                ArgumentDef::new_multi_value("sources".to_string())
+                    .max_occurences(Some(2))
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .add_argument(
+                ArgumentDef::new_single_value("target".to_string())
+                    .required(true)
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .build()
+        .unwrap();
+
+    let app = app_definition.parse_args(&["a", "b", "c"]).unwrap();
+    assert_eq!(app.values_of("sources"), Some(vec!["a", "b"]));
+    assert_eq!(app.value_of("target"), Some("c"));
+
+    let (app, files) = app_definition.parse_args_iter(["a", "b", "c"]).unwrap();
+    assert_eq!(app.value_of("target"), Some("c"));
+    assert_eq!(files.name(), None);
+
+    let app = app_definition.parse_args(&["a", "b"]);
+    assert!(app.is_err());
+
+    let app = app_definition.parse_args(&["a", "b", "c", "d"]);
+    assert!(app.is_err());
+}
+
+#[test]
+fn test_app_with_streamed_argument_like_collected() {
+    let app_definition = AppDefBuilder::new("rm".to_string())
+        .add_flag(
+                BooleanFlagDefBuilder::new("force".to_string(), Some('f'), Some("force".to_string()))
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .add_argument(
+                ArgumentDef::new_multi_value("files".to_string())
+                    .stream(true)
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .build()
+        .unwrap();
+
+    for args in [
+        vec!["a", "b"],
+        vec!["-f", "a", "b"],
+        vec!["a", "--", "-b", "--force"],
+        vec!["--", "--force", "--", "b"],
+    ] {
+        let app = app_definition.parse_args(&args).unwrap();
+        let (streamed, files) = app_definition.parse_args_iter(args.clone()).unwrap();
+        assert_eq!(streamed.boolean_flags.get("force"), app.boolean_flags.get("force"));
+        let files = files.collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(Some(files.iter().map(|f| f.to_str().unwrap()).collect()), app.values_of("files"));
+    }
+
+    // too late to set the flag once the values are streamed
+    let app = app_definition.parse_args(&["a", "--force", "b"]).unwrap();
+    assert_eq!(app.boolean_flags.get("force"), Some(&true));
+    let (_, files) = app_definition.parse_args_iter(vec!["a", "--force", "b"]).unwrap();
+    assert_eq!(
+        files.collect::<Vec<_>>(),
+        vec![Ok("a".into()), Err("option --force cannot follow the streamed values of files".to_string())]
+    );
+}
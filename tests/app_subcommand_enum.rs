@@ -0,0 +1,153 @@
+use rust_yap::{
+    AppDefBuilder, ArgumentDef, BasicSubCommandDefBuilder, BooleanFlagDefBuilder, SubCommandChainDefBuilder,
+    SubCommandEnumDefBuilder,
+};
+
+
+#[test]
+fn test_app_with_subcommand_enum() {
+    let app_definition = AppDefBuilder::new("git".to_string())
+        .subcommand(Some(Box::new(
+                SubCommandEnumDefBuilder::new()
+                    .add_subcommand(
+                            BasicSubCommandDefBuilder::new("commit".to_string())
+                                .add_flag(
+                                        BooleanFlagDefBuilder::new("all".to_string(), Some('a'), Some("all".to_string()))
+                                            .build()
+                                            .unwrap()
+                                            .into()
+                                )
+                                .build()
+                                .unwrap()
+                                .into()
+                    )
+                    .add_subcommand(
+                            BasicSubCommandDefBuilder::new("push".to_string())
+                                .add_argument(
+                                        ArgumentDef::new_single_value("target".to_string())
+                                            .build()
+                                            .unwrap()
+                                            .into()
+                                )
+                                .build()
+                                .unwrap()
+                                .into()
+                    )
+                    .add_subcommand(
+                            BasicSubCommandDefBuilder::new("remote".to_string())
+                                .subcommand(Some(Box::new(
+                                        SubCommandEnumDefBuilder::new()
+                                            .add_subcommand(
+                                                    BasicSubCommandDefBuilder::new("add".to_string())
+                                                        .add_argument(
+                                                                ArgumentDef::new_single_value("target".to_string())
+                                                                    .build()
+                                                                    .unwrap()
+                                                                    .into()
+                                                        )
+                                                        .build()
+                                                        .unwrap()
+                                                        .into()
+                                            )
+                                            .add_subcommand(
+                                                    BasicSubCommandDefBuilder::new("remove".to_string())
+                                                        .add_argument(
+                                                                ArgumentDef::new_single_value("target".to_string())
+                                                                    .build()
+                                                                    .unwrap()
+                                                                    .into()
+                                                        )
+                                                        .build()
+                                                        .unwrap()
+                                                        .into()
+                                            )
+                                            .build()
+                                            .unwrap()
+                                            .into()
+                                )))
+                                .build()
+                                .unwrap()
+                                .into()
+                    )
+                    .required(true)
+                    .build()
+                    .unwrap()
+                    .into()
+        )))
+        .build()
+        .unwrap();
+
+    let app = app_definition.parse_args(&["commit", "-a"]).unwrap();
+    let (name, commit) = app.subcommand().unwrap();
+    assert_eq!(name, "commit");
+    assert_eq!(commit.boolean_flags.get("all"), Some(&true));
+
+    let app = app_definition.parse_args(&["push", "origin"]).unwrap();
+    assert_eq!(app.subcommand().unwrap().0, "push");
+    assert_eq!(app.subcommand().unwrap().1.value_of("target"), Some("origin"));
+
+    let app = app_definition.parse_args(&["remote", "remove", "origin"]).unwrap();
+    let (name, remote) = app.subcommand().unwrap();
+    assert_eq!(name, "remote");
+    assert_eq!(remote.subcommand().unwrap().0, "remove");
+    let app = app_definition.parse_args(&["remote"]).unwrap();
+    assert!(app.subcommand().unwrap().1.subcommand().is_none());
+
+    let app = app_definition.parse_args(&[]);
+    assert_eq!(app.unwrap_err(), "one of subcommands commit, push, remote is required");
+    let app = app_definition.parse_args(&["pull"]);
+    assert_eq!(app.unwrap_err(), "unknown subcommand: pull, expected one of: commit, push, remote");
+    let app = app_definition.parse_args(&["remote", "rename"]);
+    assert_eq!(app.unwrap_err(), "unknown subcommand: rename, expected one of: add, remove");
+}
+
+#[test]
+fn test_app_with_subcommand_enum_in_chain() {
+    let app_definition = AppDefBuilder::new("tool".to_string())
+        .subcommand(Some(Box::new(
+                SubCommandChainDefBuilder::new(
+                    "run".to_string(),
+                    SubCommandEnumDefBuilder::new()
+                        .add_subcommand(
+                                BasicSubCommandDefBuilder::new("build".to_string())
+                                    .add_argument(
+                                            ArgumentDef::new_single_value("target".to_string())
+                                                .build()
+                                                .unwrap()
+                                                .into()
+                                    )
+                                    .build()
+                                    .unwrap()
+                                    .into()
+                        )
+                        .add_subcommand(
+                                BasicSubCommandDefBuilder::new("test".to_string())
+                                    .add_argument(
+                                            ArgumentDef::new_single_value("target".to_string())
+                                                .build()
+                                                .unwrap()
+                                                .into()
+                                    )
+                                    .build()
+                                    .unwrap()
+                                    .into()
+                        )
+                        .build()
+                        .unwrap()
+                        .into()
+                )
+                    .build()
+                    .unwrap()
+                    .into()
+        )))
+        .build()
+        .unwrap();
+
+    let app = app_definition.parse_args(&["run", "build", "a", "test", "build", "b"]).unwrap();
+    let (_, run) = app.subcommand().unwrap();
+    let links: Vec<&str> = run.subcommands.iter().map(|link| link.name.as_str()).collect();
+    assert_eq!(links, vec!["build", "test", "build"]);
+    assert_eq!(run.subcommands[0].value_of("target"), Some("a"));
+    assert_eq!(run.subcommands[1].value_of("target"), None);
+    assert_eq!(run.subcommands[2].value_of("target"), Some("b"));
+}
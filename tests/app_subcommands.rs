@@ -0,0 +1,136 @@
+use rust_yap::{
+    App, AppDefBuilder, ArgumentDef, BasicSubCommandDefBuilder, BooleanFlagDefBuilder, Matching,
+    OptionDef,
+};
+
+
+#[test]
+fn test_app_with_nested_subcommands() {
+    let app_definition = AppDefBuilder::new("git".to_string())
+        .abbreviations(true)
+        .subcommand(Some(Box::new(
+                BasicSubCommandDefBuilder::new("remote".to_string())
+                    .add_flag(
+                            BooleanFlagDefBuilder::new("verbose".to_string(), Some('v'), Some("verbose".to_string()))
+                                .build()
+                                .unwrap()
+                                .into()
+                    )
+                    .subcommand(Some(Box::new(
+                            BasicSubCommandDefBuilder::new("add".to_string())
+                                .add_flag(
+                                        BooleanFlagDefBuilder::new("fetch".to_string(), Some('f'), Some("fetch".to_string()))
+                                            .build()
+                                            .unwrap()
+                                            .into()
+                                )
+                                .add_option(
+                                        OptionDef::new_single_value("track".to_string(), Some('t'), Some("track".to_string()))
+                                            .build()
+                                            .unwrap()
+                                            .into()
+                                )
+                                .add_argument(
+                                        ArgumentDef::new_single_value("name".to_string())
+                                            .required(true)
+                                            .build()
+                                            .unwrap()
+                                            .into()
+                                )
+                                .add_argument(
+                                        ArgumentDef::new_single_value("url".to_string())
+                                            .required(true)
+                                            .build()
+                                            .unwrap()
+                                            .into()
+                                )
+                                .validators(Some(vec![|app: &App| {
+                                    if app.value_of("name") == Some("origin") && app.value_of("track").is_some() {
+                                        Err("origin cannot be tracked".to_string())
+                                    } else {
+                                        Ok(())
+                                    }
+                                }]))
+                                .build()
+                                .unwrap()
+                                .into()
+                    )))
+                    .build()
+                    .unwrap()
+                    .into()
+        )))
+        .build()
+        .unwrap();
+
+    let app = app_definition.parse_args(&["-V", "remote", "-v", "add", "-f", "--track", "main", "upstream", "url"]).unwrap();
+    assert_eq!(app.boolean_flags.get("verbose"), Some(&true));
+    let (name, remote) = app.subcommand().unwrap();
+    assert_eq!(name, "remote");
+    assert_eq!(remote.boolean_flags.get("verbose"), Some(&true));
+    let (name, add) = remote.subcommand().unwrap();
+    assert_eq!(name, "add");
+    assert_eq!(add.boolean_flags.get("fetch"), Some(&true));
+    assert_eq!(add.value_of("track"), Some("main"));
+    assert_eq!(add.value_of("name"), Some("upstream"));
+    assert_eq!(add.value_of("url"), Some("url"));
+    assert!(add.subcommand().is_none());
+
+    let app = app_definition.parse_args(&[]).unwrap();
+    assert!(app.subcommand().is_none());
+    let app = app_definition.parse_args(&["remote"]).unwrap();
+    assert_eq!(app.subcommand().unwrap().0, "remote");
+
+    let app = app_definition.parse_args(&["rem", "a", "origin", "url"]).unwrap();
+    assert_eq!(app.abbreviations.get("rem"), Some(&"remote".to_string()));
+    assert_eq!(app.subcommand().unwrap().1.abbreviations.get("a"), Some(&"add".to_string()));
+
+    let app = app_definition.parse_args(&["remote", "add", "-t", "main", "origin", "url"]);
+    assert_eq!(app.unwrap_err(), "origin cannot be tracked");
+    let app = app_definition.parse_args(&["remote", "add", "origin"]);
+    assert_eq!(app.unwrap_err(), "required positional argument: url is missing");
+    let app = app_definition.parse_args(&["remote", "-f", "add", "origin", "url"]);
+    assert_eq!(app.unwrap_err(), "unknown option: -f");
+    let app = app_definition.parse_args(&["push"]);
+    assert_eq!(app.unwrap_err(), "unknown subcommand: push, expected one of: remote");
+
+    let (app, errors) = app_definition.parse_args_recover(&["remote", "rm", "add", "origin"]);
+    assert_eq!(
+        errors,
+        vec![
+            "unknown subcommand: rm, expected one of: add".to_string(),
+            "required positional argument: url is missing".to_string()
+        ]
+    );
+    let (_, remote) = app.subcommand().unwrap();
+    assert_eq!(remote.subcommand().unwrap().1.value_of("name"), Some("origin"));
+}
+
+#[test]
+fn test_app_with_required_subcommand() {
+    let app_definition = AppDefBuilder::new("tool".to_string())
+        .matching(Matching { ignore_case: true, ignore_separators: true })
+        .add_argument(
+                ArgumentDef::new_single_value("target".to_string())
+                    .required(true)
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .subcommand(Some(Box::new(
+                BasicSubCommandDefBuilder::new("run_all".to_string())
+                    .required(true)
+                    .build()
+                    .unwrap()
+                    .into()
+        )))
+        .build()
+        .unwrap();
+
+    let app = app_definition.parse_args(&["here", "Run-All"]).unwrap();
+    assert_eq!(app.value_of("target"), Some("here"));
+    assert_eq!(app.subcommand().unwrap().0, "run_all");
+    assert!(app.abbreviations.is_empty());
+
+    let app = app_definition.parse_args(&["here"]);
+    assert_eq!(app.unwrap_err(), "subcommand run_all is required");
+}
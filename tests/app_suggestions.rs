@@ -0,0 +1,127 @@
+use rust_yap::{AppDefBuilder, BasicSubCommandDefBuilder, BooleanFlagDefBuilder, OptionDef, PossibleValue, SubCommandEnumDefBuilder, Syntax};
+
+
+#[test]
+fn test_app_with_suggestions() {
+    let app_definition = AppDefBuilder::new("git".to_string())
+        .syntax(Syntax { plus_flags: true, single_dash_long: true, ..Syntax::default() })
+        .add_flag(
+                BooleanFlagDefBuilder::new("paginate".to_string(), Some('p'), Some("paginate".to_string()))
+                    .global(true)
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .add_option(
+                OptionDef::new_single_value("color".to_string(), None, Some("color".to_string()))
+                    .possible_values(vec![
+                        PossibleValue::new("always".to_string(), None),
+                        PossibleValue::new("never".to_string(), None),
+                    ])
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .subcommand(Some(Box::new(
+                SubCommandEnumDefBuilder::new()
+                    .add_subcommand(
+                            BasicSubCommandDefBuilder::new("status".to_string())
+                                .add_flag(
+                                        BooleanFlagDefBuilder::new("short".to_string(), Some('s'), Some("short".to_string()))
+                                            .build()
+                                            .unwrap()
+                                            .into()
+                                )
+                                .build()
+                                .unwrap()
+                                .into()
+                    )
+                    .add_subcommand(
+                            BasicSubCommandDefBuilder::new("stash".to_string())
+                                .build()
+                                .unwrap()
+                                .into()
+                    )
+                    .build()
+                    .unwrap()
+                    .into()
+        )))
+        .build()
+        .unwrap();
+
+    let app = app_definition.parse_args(&["--colour", "always"]);
+    assert_eq!(app.unwrap_err(), "unknown option: --colour, did you mean --color?");
+    let app = app_definition.parse_args(&["+pagniate"]);
+    assert_eq!(app.unwrap_err(), "unknown flag: +pagniate, did you mean +paginate?");
+    let app = app_definition.parse_args(&["--color", "alwyas"]);
+    assert_eq!(app.unwrap_err(), "option color expects one of always, never, got: alwyas, did you mean always?");
+    let app = app_definition.parse_args(&["stauts"]);
+    assert_eq!(app.unwrap_err(), "unknown subcommand: stauts, did you mean status?");
+
+    let app = app_definition.parse_args(&["status", "--shrot"]);
+    assert_eq!(app.unwrap_err(), "unknown option: --shrot, did you mean --short?");
+    let app = app_definition.parse_args(&["status", "--paginat"]);
+    assert_eq!(app.unwrap_err(), "unknown option: --paginat, did you mean --paginate?");
+}
+
+#[test]
+fn test_app_without_suggestions() {
+    let app_definition = AppDefBuilder::new("git".to_string())
+        .syntax(Syntax { plus_flags: true, single_dash_long: true, ..Syntax::default() })
+        .add_flag(
+                BooleanFlagDefBuilder::new("paginate".to_string(), Some('p'), Some("paginate".to_string()))
+                    .global(true)
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .add_option(
+                OptionDef::new_single_value("color".to_string(), None, Some("color".to_string()))
+                    .possible_values(vec![
+                        PossibleValue::new("always".to_string(), None),
+                        PossibleValue::new("never".to_string(), None),
+                    ])
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .subcommand(Some(Box::new(
+                SubCommandEnumDefBuilder::new()
+                    .add_subcommand(
+                            BasicSubCommandDefBuilder::new("status".to_string())
+                                .add_flag(
+                                        BooleanFlagDefBuilder::new("short".to_string(), Some('s'), Some("short".to_string()))
+                                            .build()
+                                            .unwrap()
+                                            .into()
+                                )
+                                .build()
+                                .unwrap()
+                                .into()
+                    )
+                    .add_subcommand(
+                            BasicSubCommandDefBuilder::new("stash".to_string())
+                                .build()
+                                .unwrap()
+                                .into()
+                    )
+                    .build()
+                    .unwrap()
+                    .into()
+        )))
+        .build()
+        .unwrap();
+
+    // suggestions only come from the level being parsed
+    let app = app_definition.parse_args(&["--shrot"]);
+    assert_eq!(app.unwrap_err(), "unknown option: --shrot");
+    let app = app_definition.parse_args(&["stash", "--colr", "never"]);
+    assert_eq!(app.unwrap_err(), "unknown option: --colr");
+
+    let app = app_definition.parse_args(&["--size"]);
+    assert_eq!(app.unwrap_err(), "unknown option: --size");
+    let app = app_definition.parse_args(&["+color"]);
+    assert_eq!(app.unwrap_err(), "unknown flag: +color");
+    let app = app_definition.parse_args(&["commit"]);
+    assert_eq!(app.unwrap_err(), "unknown subcommand: commit, expected one of: status, stash");
+}
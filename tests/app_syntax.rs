@@ -0,0 +1,103 @@
+use rust_yap::{AppDefBuilder, BooleanFlagDefBuilder, OptionDef, Syntax};
+
+
+#[test]
+fn test_app_with_plus_flags() {
+    let app_definition = AppDefBuilder::new("set".to_string())
+        .add_flag(
+                BooleanFlagDefBuilder::new("xtrace".to_string(), Some('x'), Some("xtrace".to_string()))
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .syntax(Syntax { plus_flags: true, ..Syntax::default() })
+        .build()
+        .unwrap();
+
+    let app = app_definition.parse_args(&["-x"]).unwrap();
+    assert_eq!(app.boolean_flags.get("xtrace"), Some(&true));
+
+    let app = app_definition.parse_args(&["+x"]).unwrap();
+    assert_eq!(app.boolean_flags.get("xtrace"), Some(&false));
+
+    let app = app_definition.parse_args(&["+xV"]).unwrap();
+    assert_eq!(app.boolean_flags.get("xtrace"), Some(&false));
+    assert_eq!(app.boolean_flags.get("verbose"), Some(&false));
+
+    let app = app_definition.parse_args(&["+x", "-x"]);
+    assert!(app.is_err());
+}
+
+#[test]
+fn test_app_with_single_dash_long_options() {
+    let app_definition = AppDefBuilder::new("find".to_string())
+        .add_option(
+                OptionDef::new_single_value("name".to_string(), None, Some("name".to_string()))
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .syntax(Syntax { single_dash_long: true, ..Syntax::default() })
+        .build()
+        .unwrap();
+
+    let app = app_definition.parse_args(&["-name", "*.rs", "-verbose"]).unwrap();
+    assert_eq!(app.value_of("name"), Some("*.rs"));
+    assert_eq!(app.boolean_flags.get("verbose"), Some(&true));
+}
+
+#[test]
+fn test_app_with_single_dash_long_and_short_names() {
+    let app_definition = AppDefBuilder::new("find".to_string())
+        .add_flag(
+                BooleanFlagDefBuilder::new("follow".to_string(), Some('L'), None)
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .add_option(
+                OptionDef::new_single_value("name".to_string(), None, Some("name".to_string()))
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .add_option(
+                OptionDef::new_single_value("type".to_string(), Some('t'), Some("type".to_string()))
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .syntax(Syntax { single_dash_long: true, ..Syntax::default() })
+        .build()
+        .unwrap();
+
+    let app = app_definition.parse_args(&["-L", "-name", "*.rs", "-V", "-t=f"]).unwrap();
+    assert_eq!(app.boolean_flags.get("follow"), Some(&true));
+    assert_eq!(app.value_of("name"), Some("*.rs"));
+    assert_eq!(app.boolean_flags.get("verbose"), Some(&true));
+    assert_eq!(app.value_of("type"), Some("f"));
+
+    let app = app_definition.parse_args(&["-x"]);
+    assert_eq!(app.unwrap_err(), "unknown option: -x");
+}
+
+#[test]
+fn test_app_with_slash_options() {
+    let app_definition = AppDefBuilder::new("dir".to_string())
+        .add_option(
+                OptionDef::new_single_value("out".to_string(), Some('o'), Some("out".to_string()))
+                    .build()
+                    .unwrap()
+                    .into()
+        )
+        .syntax(Syntax { slash_options: true, ..Syntax::default() })
+        .build()
+        .unwrap();
+
+    let app = app_definition.parse_args(&["/out:file.txt", "/V"]).unwrap();
+    assert_eq!(app.value_of("out"), Some("file.txt"));
+    assert_eq!(app.boolean_flags.get("verbose"), Some(&true));
+
+    let app = app_definition.parse_args(&["/o", "file.txt"]).unwrap();
+    assert_eq!(app.value_of("out"), Some("file.txt"));
+}
@@ -1,3 +1,27 @@
 extern crate rust_yap;
 
 mod app_arguments;
+mod app_options;
+mod app_os_args;
+mod app_stdio;
+mod app_hyphen_values;
+mod app_syntax;
+mod app_response_files;
+mod app_streaming;
+mod app_parse_str;
+mod app_recovery;
+mod app_abbreviations;
+mod app_matching;
+mod app_subcommands;
+mod app_chains;
+mod app_chains_mixed;
+mod app_subcommand_enum;
+mod app_separator;
+mod app_argument_order;
+mod app_global;
+mod app_defaults;
+mod app_env;
+mod app_config;
+mod app_possible_values;
+mod app_suggestions;
+mod app_relations;